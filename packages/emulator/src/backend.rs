@@ -1,10 +1,12 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use input::ButtonEvent;
-use ipc::SOCKET_PATH;
+use ipc::socket_path;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
 use tokio::sync::{mpsc, oneshot};
 
-use crate::{ipc::server, MAIN_THREAD, PARK_MAIN};
+use crate::{events, ipc::server, MAIN_THREAD, PARK_MAIN};
 
 pub enum BackendMessage {}
 
@@ -26,7 +28,15 @@ pub fn start() -> (mpsc::Sender<BackendMessage>, mpsc::Receiver<ButtonEvent>) {
             // Spawn input task
             tokio::spawn(task);
 
-            tokio::fs::remove_file(SOCKET_PATH).await.ok();
+            // Does nothing unless logs are forwarded to the os
+            tokio::spawn(logging::forward_task());
+            tokio::spawn(events::task());
+
+            let socket = socket_path();
+            if let Some(dir) = socket.parent() {
+                tokio::fs::create_dir_all(dir).await.ok();
+            }
+            tokio::fs::remove_file(socket).await.ok();
 
             // Spawn ipc server
             tokio::spawn(server(server_send));
@@ -40,21 +50,58 @@ pub fn start() -> (mpsc::Sender<BackendMessage>, mpsc::Receiver<ButtonEvent>) {
     (send, recv_input_recv.blocking_recv().unwrap())
 }
 
+/// Main keeps parking itself until this is cleared
 static MAIN_PARKED: AtomicBool = AtomicBool::new(false);
+/// How many `MainParked` guards are alive, main runs again once the last is dropped
+static PARKERS: Mutex<usize> = Mutex::new(0);
+/// Only one caller asks main to park and waits for it, the rest wait for them
+static PARKING: Lazy<tokio::sync::Mutex<()>> = Lazy::new(Default::default);
 
-pub fn main_parked() -> bool {
-    MAIN_PARKED.load(Ordering::Relaxed)
-}
+/// Main thread is parked and can't call into the core while this is alive
+pub struct MainParked(());
 
-pub async fn park_main() {
-    if !main_parked() {
-        PARK_MAIN.get().unwrap().send(()).await.unwrap();
-        MAIN_PARKED.store(true, Ordering::Relaxed);
+impl Drop for MainParked {
+    fn drop(&mut self) {
+        let mut parkers = PARKERS.lock();
+        *parkers -= 1;
+        if *parkers == 0 {
+            MAIN_PARKED.store(false, Ordering::Release);
+            MAIN_THREAD.get().unwrap().unpark();
+        }
     }
 }
 
-pub fn unpark_main() {
-    if main_parked() {
-        MAIN_THREAD.get().unwrap().unpark();
+/// Returns once the main thread has actually parked, it stays parked until the guard is dropped
+pub async fn park_main() -> MainParked {
+    // Spawned so a cancelled caller can't leave main parked, the guard is just dropped instead
+    tokio::spawn(async {
+        let _parking = PARKING.lock().await;
+
+        {
+            let mut parkers = PARKERS.lock();
+            if *parkers > 0 {
+                *parkers += 1;
+                return MainParked(());
+            }
+        }
+
+        let (parked_send, parked_recv) = oneshot::channel();
+        PARK_MAIN.get().unwrap().send(parked_send).await.unwrap();
+        parked_recv.await.unwrap();
+
+        *PARKERS.lock() += 1;
+        MainParked(())
+    })
+    .await
+    .unwrap()
+}
+
+/// Called by the main thread when asked to park, returns once every guard is dropped
+pub fn park_here(parked: oneshot::Sender<()>) {
+    MAIN_PARKED.store(true, Ordering::Release);
+    parked.send(()).ok();
+    // Parking can wake up spuriously
+    while MAIN_PARKED.load(Ordering::Acquire) {
+        std::thread::park();
     }
 }
@@ -0,0 +1,132 @@
+//! Routes the core's printf style log messages into tracing, with `core::<name>` as the target
+
+use std::{
+    ffi::{c_char, c_int, CStr, VaList, VaListImpl},
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::ARGS;
+
+/// Env var with the lowest core log level to keep, one of `off`, `error`, `warn`, `info` or `debug`
+pub const CORE_LOG_ENV: &str = "OXIDE_CORE_LOG";
+
+/// Messages allowed per `RATE_WINDOW`, the rest are counted and dropped
+const RATE_LIMIT: u32 = 30;
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+/// Most messages fit, longer ones are formatted again into a big enough buffer
+const BUF_SIZE: usize = 512;
+
+static FILTER: Lazy<log::LevelFilter> = Lazy::new(|| match std::env::var(CORE_LOG_ENV) {
+    Ok(level) => level.parse().unwrap_or_else(|_| {
+        tracing::warn!("Unknown {CORE_LOG_ENV} `{level}`, using info");
+        log::LevelFilter::Info
+    }),
+    Err(_) => log::LevelFilter::Info,
+});
+
+static RATE: Mutex<Option<Rate>> = parking_lot::const_mutex(None);
+
+struct Rate {
+    window_start: Instant,
+    count: u32,
+    dropped: u32,
+}
+
+extern "C" {
+    fn vsnprintf(buf: *mut c_char, size: usize, format: *const c_char, args: VaList) -> c_int;
+}
+
+pub unsafe extern "C" fn log(
+    level: libretro_sys::LogLevel,
+    format_str: *const c_char,
+    mut args: ...
+) {
+    let level = match level {
+        libretro_sys::LogLevel::Debug => log::Level::Debug,
+        libretro_sys::LogLevel::Info => log::Level::Info,
+        libretro_sys::LogLevel::Warn => log::Level::Warn,
+        libretro_sys::LogLevel::Error => log::Level::Error,
+    };
+
+    if level > *FILTER || format_str.is_null() {
+        return;
+    }
+
+    let Some(dropped) = allow() else {
+        return;
+    };
+
+    let message = format(format_str, &mut args);
+    let target = format!(
+        "core::{}",
+        ARGS.get().map_or("unknown", |args| args.core_name())
+    );
+
+    if dropped > 0 {
+        emit(
+            log::Level::Warn,
+            &target,
+            format_args!("Dropped {dropped} messages from a chatty core"),
+        );
+    }
+    emit(level, &target, format_args!("{}", message.trim_end()));
+}
+
+/// Returns how many messages were dropped before this one, or `None` if this one should be
+fn allow() -> Option<u32> {
+    let mut rate = RATE.lock();
+    let now = Instant::now();
+    let rate = rate.get_or_insert(Rate {
+        window_start: now,
+        count: 0,
+        dropped: 0,
+    });
+
+    if now.duration_since(rate.window_start) >= RATE_WINDOW {
+        rate.window_start = now;
+        rate.count = 0;
+    }
+
+    if rate.count >= RATE_LIMIT {
+        rate.dropped += 1;
+        return None;
+    }
+
+    rate.count += 1;
+    Some(std::mem::take(&mut rate.dropped))
+}
+
+/// Renders the format string with vsnprintf
+unsafe fn format(format_str: *const c_char, args: &mut VaListImpl) -> String {
+    let mut buf = [0 as c_char; BUF_SIZE];
+    // The first attempt may use up the args, so keep a copy for a retry
+    let mut retry = args.clone();
+    let len = vsnprintf(buf.as_mut_ptr(), BUF_SIZE, format_str, args.as_va_list());
+
+    if len < 0 {
+        return CStr::from_ptr(format_str).to_string_lossy().into_owned();
+    }
+
+    if (len as usize) < BUF_SIZE {
+        return CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned();
+    }
+
+    let mut buf = vec![0 as c_char; len as usize + 1];
+    vsnprintf(buf.as_mut_ptr(), buf.len(), format_str, retry.as_va_list());
+    CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()
+}
+
+/// Goes through `log` since tracing targets have to be known at compile time.
+/// The subscriber picks these up with the target intact, levels are already filtered here
+fn emit(level: log::Level, target: &str, args: std::fmt::Arguments) {
+    log::logger().log(
+        &log::Record::builder()
+            .level(level)
+            .target(target)
+            .args(args)
+            .build(),
+    );
+}
@@ -1,6 +1,7 @@
 //! Mostly implemented thanks to https://www.retroreversing.com/CreateALibRetroFrontEndInRust
 
 pub mod audio;
+mod log;
 mod render;
 pub mod save;
 mod variable;
@@ -18,12 +19,15 @@ use std::{
 
 use arc_swap::ArcSwapOption;
 use fixed_map::Map;
+use ipc::functions::Event;
 use libloading::Library;
 use libretro_sys::{CoreAPI, GameInfo, PixelFormat, SystemAvInfo};
 use once_cell::sync::OnceCell;
 use winit::window::Window;
 
-use crate::{convert, Button, ARGS};
+use layout::layout;
+
+use crate::{convert, events, Button, ARGS};
 
 use self::variable::VariableDef;
 
@@ -227,14 +231,19 @@ unsafe extern "C" fn libretro_environment_callback(command: u32, data: *mut c_vo
             *(data as *mut bool) = true;
             tracing::debug!("ENVIRONMENT_GET_CAN_DUPE");
         }
-        libretro_sys::ENVIRONMENT_GET_SYSTEM_DIRECTORY
-        | libretro_sys::ENVIRONMENT_GET_SAVE_DIRECTORY => {
-            let dir = CString::new(ARGS.get().unwrap().sys_dir()).unwrap();
-            *(data as *mut *const u8) = dir.as_ptr();
-
-            // Don't free this memory while core may use it??? (idk if core copies it or nah)
-            std::mem::forget(dir);
-
+        libretro_sys::ENVIRONMENT_GET_SYSTEM_DIRECTORY => {
+            // Cores may hold on to the pointer, so it lives as long as the process
+            static SYSTEM_DIR: OnceCell<CString> = OnceCell::new();
+            let dir = SYSTEM_DIR
+                .get_or_init(|| CString::new(layout().bios().as_os_str().as_bytes()).unwrap());
+            *(data as *mut *const c_char) = dir.as_ptr();
+            return true;
+        }
+        libretro_sys::ENVIRONMENT_GET_SAVE_DIRECTORY => {
+            static SAVE_DIR: OnceCell<CString> = OnceCell::new();
+            let dir =
+                SAVE_DIR.get_or_init(|| CString::new(ARGS.get().unwrap().save_dir()).unwrap());
+            *(data as *mut *const c_char) = dir.as_ptr();
             return true;
         }
         libretro_sys::ENVIRONMENT_SET_PIXEL_FORMAT => {
@@ -261,11 +270,16 @@ unsafe extern "C" fn libretro_environment_callback(command: u32, data: *mut c_vo
             *(data as *mut bool) = false;
             return true;
         }
+        libretro_sys::ENVIRONMENT_SHUTDOWN => {
+            // The os stops the emulator, after saving
+            events::send(Event::ShutdownRequested);
+            return true;
+        }
         libretro_sys::ENVIRONMENT_GET_LOG_INTERFACE => {
             let cb = &mut *(data as *mut libretro_sys::LogCallback);
             // SAFETY: libretro_sys has the wrong type here as it is actually variadic
             cb.log = std::mem::transmute::<_, _>(
-                log as unsafe extern "C" fn(libretro_sys::LogLevel, *const u8, ...),
+                log::log as unsafe extern "C" fn(libretro_sys::LogLevel, *const c_char, ...),
             );
             return true;
         }
@@ -329,14 +343,3 @@ unsafe extern "C" fn libretro_set_input_state_callback(
 }
 
 unsafe extern "C" fn libretro_set_audio_sample_callback(left: i16, right: i16) {}
-
-unsafe extern "C" fn log(level: libretro_sys::LogLevel, format_str: *const c_char, args: ...) {
-    //GBA DMA: Starting DMA 3 0x03007E44 -> 0x02000000 (8500:0000)
-    //nix::libc::printf(format_str, args);
-    match level {
-        libretro_sys::LogLevel::Info => tracing::info!("Core"),
-        libretro_sys::LogLevel::Error => tracing::error!("Core"),
-        libretro_sys::LogLevel::Warn => tracing::warn!("Core"),
-        libretro_sys::LogLevel::Debug => {}
-    }
-}
@@ -224,6 +224,8 @@ pub fn render(mut buffer: softbuffer::Buffer<'_, &Window, &Window>) {
 
     // Store a copy of frame for screenshots
     super::CURRENT_FRAME.store(Some(Arc::new(buffer.to_vec())));
+    // Drawn after the copy so overlays don't end up in screenshots
+    crate::overlay::draw(&mut buffer, 640, 480);
     buffer.present().unwrap();
     RENDERED.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 }
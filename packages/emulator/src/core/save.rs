@@ -1,24 +1,16 @@
-use std::{
-    ffi::c_void,
-    io::{self, BufWriter, Read},
-};
+use std::{ffi::c_void, io};
 
-use tokio::io::AsyncWriteExt;
-
-use crate::{
-    backend::{park_main, unpark_main},
-    convert, ARGS,
-};
+use crate::{backend::park_main, convert, ARGS};
 
 use super::{CORE, CURRENT_FRAME};
 
 /// Saves to save dir with provided slot or `auto` if none is provided
 ///
-/// Calls save_inner while making sure main thread is parked before and unparked after
+/// Makes sure main thread is parked while serializing and unparked after
 pub async fn save(slot: Option<usize>) -> io::Result<()> {
     tracing::info!("saving...");
 
-    park_main().await;
+    let parked = park_main().await;
     let save_data = tokio::task::spawn_blocking(move || {
         let buf_size = unsafe { (CORE.get().unwrap().retro_serialize_size)() };
         tracing::debug!("Save size: {buf_size}");
@@ -30,7 +22,7 @@ pub async fn save(slot: Option<usize>) -> io::Result<()> {
         };
 
         // Allow main thread to continue execution once serialize is complete
-        unpark_main();
+        drop(parked);
 
         if !serialize_res {
             tracing::error!("retro_serialize failed twice, error out.");
@@ -51,41 +43,28 @@ pub async fn save(slot: Option<usize>) -> io::Result<()> {
 
     let slot = slot.map(|slot| slot.to_string()).unwrap_or("auto".into());
     let save_path = format!("{save_dir}/{}-{slot}.sav", args.game_name());
-    tokio::fs::remove_file(&save_path).await.ok();
-    let mut save_file = tokio::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&save_path)
-        .await
-        .unwrap();
-
     let img_path = format!("{save_path}.png");
-    let img_file = tokio::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(img_path)
-        .await
-        .unwrap();
 
     let current_frame = CURRENT_FRAME.load_full().unwrap();
     let rgba_frame = convert::xrgb8888_to_rgba888(&current_frame);
-    let mut encoder =
-        png::Encoder::new(BufWriter::new(img_file.into_std().await), 640_u32, 480_u32);
-    encoder.set_color(png::ColorType::Rgba);
-    encoder.set_depth(png::BitDepth::Eight);
-
-    // Write contents to files
-    save_file.write_all(&save_data).await?;
-    save_file.sync_data().await?;
-    tokio::task::spawn_blocking(move || {
+    let img_data = tokio::task::spawn_blocking(move || {
+        let mut img_data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut img_data, 640_u32, 480_u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
         let mut writer = encoder.write_header().unwrap();
         writer.write_image_data(&rgba_frame).unwrap();
+        writer.finish().unwrap();
+        img_data
     })
     .await
     .unwrap();
 
+    // Write contents to files, keeping the previous state around in case this one is bad
+    persist::write(save_path, save_data).await?;
+    persist::write(img_path, img_data).await?;
+
     Ok(())
 }
 
@@ -96,22 +75,80 @@ pub fn load(slot: Option<usize>) -> io::Result<()> {
 
     let slot = slot.map(|slot| slot.to_string()).unwrap_or("auto".into());
     let save_path = format!("{save_dir}/{}-{slot}.sav", args.game_name());
-    let mut save_file = std::fs::OpenOptions::new().read(true).open(&save_path)?;
-
-    let buf_size = unsafe { (CORE.get().unwrap().retro_serialize_size)() };
-    let mut save_buf = Vec::with_capacity(buf_size);
-    let bytes_read = save_file.read_to_end(&mut save_buf)?;
-    tracing::debug!("Read: {bytes_read} vs Size: {buf_size}");
-
-    let success = unsafe {
-        (CORE.get().unwrap().retro_unserialize)(save_buf.as_ptr() as *const c_void, save_buf.len())
-    };
-
-    match success {
-        true => Ok(()),
-        false => Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Core failed to load state.",
-        )),
+
+    // A state the core can't load falls back to the previous one
+    persist::read_sync_with(&save_path, |save_buf| {
+        let buf_size = unsafe { (CORE.get().unwrap().retro_serialize_size)() };
+        tracing::debug!("Read: {} vs Size: {buf_size}", save_buf.len());
+
+        let success = unsafe {
+            (CORE.get().unwrap().retro_unserialize)(
+                save_buf.as_ptr() as *const c_void,
+                save_buf.len(),
+            )
+        };
+
+        match success {
+            true => Ok(()),
+            false => Err("Core failed to load state."),
+        }
+    })
+}
+
+fn sram_path() -> String {
+    let args = ARGS.get().unwrap();
+    format!("{}/{}.srm", args.save_dir(), args.game_name())
+}
+
+/// Writes the core's battery backed save ram to disk, if the core has any.
+/// The emulator is killed rather than exited, so this has to be asked for before that
+pub async fn flush_sram() -> io::Result<()> {
+    let parked = park_main().await;
+    let sram = tokio::task::spawn_blocking(move || {
+        // SAFETY: main thread is parked, so the core isn't writing to its save ram
+        let sram = unsafe {
+            let core = CORE.get().unwrap();
+            let data = (core.retro_get_memory_data)(libretro_sys::MEMORY_SAVE_RAM);
+            let size = (core.retro_get_memory_size)(libretro_sys::MEMORY_SAVE_RAM);
+
+            if data.is_null() || size == 0 {
+                None
+            } else {
+                Some(std::slice::from_raw_parts(data as *const u8, size).to_vec())
+            }
+        };
+
+        drop(parked);
+        sram
+    })
+    .await
+    .unwrap();
+
+    match sram {
+        Some(sram) => {
+            tracing::debug!("Flushing {} bytes of sram", sram.len());
+            persist::write(sram_path(), sram).await
+        }
+        None => Ok(()),
     }
 }
+
+/// Loads save ram into the core, must be called after the game is loaded
+pub fn load_sram() -> io::Result<()> {
+    persist::read_sync_with(sram_path(), |sram| unsafe {
+        let core = CORE.get().unwrap();
+        let data = (core.retro_get_memory_data)(libretro_sys::MEMORY_SAVE_RAM);
+        let size = (core.retro_get_memory_size)(libretro_sys::MEMORY_SAVE_RAM);
+
+        if data.is_null() || size == 0 {
+            return Err("Core has no save ram");
+        }
+        if sram.len() != size {
+            tracing::warn!("Save ram is {} bytes, core expects {size}", sram.len());
+        }
+
+        let len = sram.len().min(size);
+        std::ptr::copy_nonoverlapping(sram.as_ptr(), data as *mut u8, len);
+        Ok(())
+    })
+}
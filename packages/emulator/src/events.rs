@@ -0,0 +1,92 @@
+//! Tells the os about things happening in the emulator, see `ipc::functions::Event`
+
+use std::time::{Duration, Instant};
+
+use ipc::functions::{Event, SendEvent, SendEventArgs};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+
+/// Frame rate is checked over this long
+const FPS_WINDOW: Duration = Duration::from_secs(5);
+/// Fraction of the core's frame rate below which it counts as dropped
+const FPS_DROP: f64 = 0.9;
+
+/// Sender for queueing events, and the receiver until `task` takes it
+type Events = (
+    mpsc::UnboundedSender<Event>,
+    Mutex<Option<mpsc::UnboundedReceiver<Event>>>,
+);
+
+static EVENTS: Lazy<Events> = Lazy::new(|| {
+    let (send, recv) = mpsc::unbounded_channel();
+    (send, Mutex::new(Some(recv)))
+});
+
+/// Queues an event for the os, can be called from any thread
+pub fn send(event: Event) {
+    tracing::debug!("Sending event: {event:?}");
+    EVENTS.0.send(event).ok();
+}
+
+/// Sends queued events to the os, in order
+pub async fn task() {
+    let Some(mut recv) = EVENTS.1.lock().take() else {
+        return;
+    };
+
+    while let Some(event) = recv.recv().await {
+        if let Err(err) = ipc::client::call::<SendEvent>(SendEventArgs { event }).await {
+            tracing::error!("Failed to send event: {err:?}");
+        }
+    }
+}
+
+/// Counts frames on the main thread and sends `FpsDropped` and `FpsRecovered`
+#[derive(Debug)]
+pub struct FpsMonitor {
+    target: f64,
+    window_start: Instant,
+    frames: u32,
+    dropped: bool,
+}
+
+impl FpsMonitor {
+    pub fn new(target: f64) -> Self {
+        Self {
+            target,
+            window_start: Instant::now(),
+            frames: 0,
+            dropped: false,
+        }
+    }
+
+    pub fn frame(&mut self) {
+        self.frames += 1;
+
+        let elapsed = self.window_start.elapsed();
+        if elapsed < FPS_WINDOW {
+            return;
+        }
+
+        let fps = self.frames as f64 / elapsed.as_secs_f64();
+        let dropped = fps < self.target * FPS_DROP;
+        if dropped && !self.dropped {
+            send(Event::FpsDropped {
+                fps: fps as u32,
+                target: self.target.round() as u32,
+            });
+        } else if !dropped && self.dropped {
+            send(Event::FpsRecovered);
+        }
+
+        self.dropped = dropped;
+        self.reset();
+    }
+
+    /// Starts a new window, for after the main thread was parked
+    pub fn reset(&mut self) {
+        self.window_start = Instant::now();
+        self.frames = 0;
+    }
+}
@@ -1,60 +1,100 @@
 use std::future::Future;
 
 use ipc::{
-    extract::Json,
-    functions::{Function, SaveState, SaveStateArgs, Start, StartArgs, Stop, StopArgs},
-    routing::post,
-    Router, StatusCode,
+    functions::{
+        Capabilities, Event, FlushSram, FlushSramArgs, Function, Hello, HelloArgs, SaveState,
+        SaveStateArgs, ShowOverlay, ShowOverlayArgs, Start, StartArgs, Stop, StopArgs,
+        PROTOCOL_VERSION,
+    },
+    Functions,
 };
+use parking_lot::Mutex;
 use tokio::sync::mpsc;
 
 use crate::{
-    backend::{park_main, unpark_main, BackendMessage},
-    core::save::save,
-    ARGS,
+    backend::{park_main, BackendMessage, MainParked},
+    core::save::{flush_sram, save},
+    events, overlay, ARGS,
 };
 
+/// Held from `Stop` until `Start`, saving meanwhile parks main on top of this
+static STOPPED: Mutex<Option<MainParked>> = Mutex::new(None);
+
 pub fn server(
     message_sender: mpsc::Sender<BackendMessage>,
-) -> impl Future<Output = Result<(), ipc::Error>> {
-    let router = Router::new()
-        .route(
-            SaveState::path(),
-            post(
-                |Json(SaveStateArgs { slot }): Json<<SaveState as Function>::ReqBody>| async move {
-                    match save(slot.clone()).await {
-                        Ok(_) => {
-                            // All save ops went off with no problem
-                            StatusCode::OK
-                        }
-                        Err(err) => {
-                            tracing::error!(
-                                "Error creating save state: slot: {:?} {:#?}, {err:?}",
-                                slot,
-                                ARGS
-                            );
-                            StatusCode::INTERNAL_SERVER_ERROR
-                        }
-                    }
-                },
-            ),
-        )
-        .route(
-            Stop::path(),
-            post(
-                |Json(StopArgs {}): Json<<Stop as Function>::ReqBody>| async move {
-                    park_main().await;
-                },
-            ),
-        )
-        .route(
-            Start::path(),
-            post(
-                |Json(StartArgs {}): Json<<Start as Function>::ReqBody>| async move {
-                    unpark_main();
-                },
-            ),
-        )
+) -> impl Future<Output = Result<(), ipc::ServerError>> {
+    let functions = Functions::new()
+        .function(SaveState, |SaveStateArgs { slot }| async move {
+            match save(slot.clone()).await {
+                Ok(_) => {
+                    // All save ops went off with no problem
+                    events::send(Event::Saved { slot });
+                    Ok(())
+                }
+                Err(err) => {
+                    tracing::error!(
+                        "Error creating save state: slot: {:?} {:#?}, {err:?}",
+                        slot,
+                        ARGS
+                    );
+                    events::send(Event::SaveFailed {
+                        slot,
+                        error: err.to_string(),
+                    });
+                    Err(err.into())
+                }
+            }
+        })
+        .function(FlushSram, |FlushSramArgs {}| async move {
+            match flush_sram().await {
+                Ok(_) => {
+                    events::send(Event::SramFlushed);
+                    Ok(())
+                }
+                Err(err) => {
+                    tracing::error!("Error flushing save ram: {err:?}");
+                    Err(err.into())
+                }
+            }
+        })
+        .function(Stop, |StopArgs {}| async move {
+            if STOPPED.lock().is_none() {
+                let parked = park_main().await;
+                *STOPPED.lock() = Some(parked);
+            }
+            Ok(())
+        })
+        .function(Start, |StartArgs {}| async move {
+            STOPPED.lock().take();
+            Ok(())
+        })
+        .function(ShowOverlay, |ShowOverlayArgs { overlay }| async move {
+            overlay::show(overlay);
+            Ok(())
+        });
+
+    // Everything above, plus hello itself
+    let served: Vec<String> = functions
+        .paths()
+        .iter()
+        .copied()
+        .chain([Hello::path()])
+        .map(String::from)
+        .collect();
+    let router = functions
+        .function(Hello, move |HelloArgs { version }| async move {
+            if version != PROTOCOL_VERSION {
+                tracing::warn!(
+                    "Os speaks ipc version {version}, emulator speaks {PROTOCOL_VERSION}"
+                );
+            }
+            Ok(Capabilities {
+                version: PROTOCOL_VERSION,
+                functions: served,
+                build: env!("CARGO_PKG_VERSION").into(),
+            })
+        })
+        .into_router()
         .with_state(message_sender);
 
     ipc::server::server(router)
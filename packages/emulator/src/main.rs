@@ -3,14 +3,17 @@
 mod backend;
 pub mod convert;
 pub mod core;
+mod events;
 mod ipc;
+mod overlay;
 
 use backend::BackendMessage;
 use bpaf::Bpaf;
 use fixed_map::Map;
 use input::Button;
+use layout::layout;
 use once_cell::sync::OnceCell;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use winit::{
     dpi::{LogicalPosition, LogicalSize},
     event::{Event, WindowEvent},
@@ -29,8 +32,8 @@ use std::{
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 static ARGS: OnceCell<Args> = OnceCell::new();
 static BACKEND_SENDER: OnceCell<mpsc::Sender<BackendMessage>> = OnceCell::new();
-/// Main will poll this once a frame to check if it should be parked
-static PARK_MAIN: OnceCell<mpsc::Sender<()>> = OnceCell::new();
+/// Main will poll this once a frame to check if it should be parked, and confirms once it has
+static PARK_MAIN: OnceCell<mpsc::Sender<oneshot::Sender<()>>> = OnceCell::new();
 static MAIN_THREAD: OnceCell<Thread> = OnceCell::new();
 
 #[derive(Debug, Bpaf)]
@@ -39,6 +42,9 @@ struct Args {
     #[bpaf(short, long, flag(true, false))]
     /// Use an auto save if it exists, essentially resume
     pub load_auto: bool,
+    #[bpaf(long, argument("ROOT"))]
+    /// Root to find saves and such in, defaults to the sd card
+    pub root: Option<PathBuf>,
     #[bpaf(positional)]
     /// Path to the core to use
     pub core_path: PathBuf,
@@ -62,37 +68,17 @@ impl Args {
         self.game_path.file_stem().unwrap().to_str().unwrap()
     }
 
-    pub fn sys_dir(&self) -> String {
-        format!("/mnt/SDCARD/Saves/{}", self.core_name())
-    }
-
     pub fn save_dir(&self) -> String {
-        format!("{}/saves", self.sys_dir())
+        layout().saves(self.core_name()).display().to_string()
     }
 }
 
 fn main() {
-    tracing_subscriber::fmt()
-        .with_ansi(false)
-        .compact()
-        .with_env_filter("oss=debug,emulator=debug")
-        .with_writer(
-            std::fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .open(format!(
-                    "/mnt/SDCARD/miyoo/app/emu_log_{}.log",
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::SystemTime::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs()
-                ))
-                .unwrap(),
-        )
-        .init();
-
     let args = args().run();
+    layout::init(args.root.clone());
+
+    logging::init("emulator");
+
     tracing::debug!("{args:#?}");
     ARGS.set(args).unwrap();
     MAIN_THREAD.set(std::thread::current()).unwrap();
@@ -147,6 +133,14 @@ fn main() {
     // Can be called after av_info is available
     core::audio::init();
 
+    // Load save ram before the auto save, which may overwrite it
+    if let Err(err) = save::load_sram() {
+        // Plenty of games have no save ram file yet
+        if err.kind() != std::io::ErrorKind::NotFound {
+            tracing::error!("Error loading save ram: {err:?}");
+        }
+    }
+
     if ARGS.get().unwrap().load_auto {
         if let Err(err) = save::load(None) {
             // It is valid for a auto file to not be found
@@ -158,6 +152,7 @@ fn main() {
 
     let mut input_state: Map<Button, bool> = Map::new();
     let mut last_loop_end = Instant::now();
+    let mut fps_monitor = events::FpsMonitor::new(fps);
 
     tracing::debug!("Starting event loop! :D");
     event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
@@ -178,10 +173,12 @@ fn main() {
             };
 
             // The sender half can NEVER be dropped so its okay not to handle that case
-            if let Ok(_) = park_recv.try_recv() {
+            if let Ok(parked) = park_recv.try_recv() {
                 tracing::debug!("Main thread perking...");
                 // Wait for save op to finish and unpark this thread
-                std::thread::park();
+                backend::park_here(parked);
+                // Time spent parked isn't dropped frames
+                fps_monitor.reset();
             }
 
             // Consume all inputs in channel
@@ -221,6 +218,7 @@ fn main() {
                     std::hint::spin_loop();
                 }
             }
+            fps_monitor.frame();
             last_loop_end = Instant::now();
         })
         .unwrap();
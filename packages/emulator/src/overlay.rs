@@ -0,0 +1,78 @@
+//! Level bars drawn over the game when volume or brightness is changed
+
+use std::time::{Duration, Instant};
+
+use ipc::functions::{Overlay, OverlayKind};
+use parking_lot::Mutex;
+
+/// How long an overlay stays up after the last change
+const SHOW_FOR: Duration = Duration::from_millis(1500);
+
+const BAR_WIDTH: usize = 400;
+const BAR_HEIGHT: usize = 16;
+/// Distance from the bottom of the screen
+const BAR_BOTTOM: usize = 40;
+const BORDER: usize = 2;
+
+const BACKGROUND: u32 = 0x00202020;
+const VOLUME_COLOR: u32 = 0x00F74C00;
+const BRIGHTNESS_COLOR: u32 = 0x00FFFFFF;
+const BATTERY_COLOR: u32 = 0x00FF2020;
+
+static OVERLAY: Mutex<Option<(Overlay, Instant)>> = parking_lot::const_mutex(None);
+
+/// Called from the ipc server, the overlay is drawn on the next frames
+pub fn show(overlay: Overlay) {
+    *OVERLAY.lock() = Some((overlay, Instant::now()));
+}
+
+/// Draws the current overlay, if any, into an xrgb8888 frame
+pub fn draw(buffer: &mut [u32], width: usize, height: usize) {
+    let left = width.saturating_sub(BAR_WIDTH) / 2;
+    let top = height.saturating_sub(BAR_BOTTOM + BAR_HEIGHT);
+
+    let overlay = {
+        let mut current = OVERLAY.lock();
+        match *current {
+            Some((_, shown_at)) if shown_at.elapsed() > SHOW_FOR => {
+                *current = None;
+                // The bar may be over the letterbox, which the core never draws over
+                fill(buffer, width, height, left, top, 0);
+                return;
+            }
+            Some((overlay, _)) => overlay,
+            None => return,
+        }
+    };
+
+    let filled = if overlay.max == 0 {
+        0
+    } else {
+        (BAR_WIDTH - BORDER * 2) * overlay.level.min(overlay.max) as usize / overlay.max as usize
+    };
+    let color = match overlay.kind {
+        OverlayKind::Volume => VOLUME_COLOR,
+        OverlayKind::Brightness => BRIGHTNESS_COLOR,
+        OverlayKind::Battery => BATTERY_COLOR,
+    };
+
+    for y in top..(top + BAR_HEIGHT).min(height) {
+        let row = &mut buffer[y * width..(y + 1) * width];
+        for x in left..(left + BAR_WIDTH).min(width) {
+            let inside = y >= top + BORDER
+                && y < top + BAR_HEIGHT - BORDER
+                && x >= left + BORDER
+                && x < left + BORDER + filled;
+            row[x] = if inside { color } else { BACKGROUND };
+        }
+    }
+}
+
+fn fill(buffer: &mut [u32], width: usize, height: usize, left: usize, top: usize, color: u32) {
+    for y in top..(top + BAR_HEIGHT).min(height) {
+        let row = &mut buffer[y * width..(y + 1) * width];
+        for pixel in &mut row[left..(left + BAR_WIDTH).min(width)] {
+            *pixel = color;
+        }
+    }
+}
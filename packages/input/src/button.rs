@@ -1,6 +1,9 @@
 use evdev::Key;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, fixed_map::Key)]
+use crate::Keymap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, fixed_map::Key, Serialize, Deserialize)]
 pub enum Button {
     Up,
     Down,
@@ -23,31 +26,30 @@ pub enum Button {
 }
 
 impl Button {
+    /// Maps a key from the Miyoo's built in buttons
     pub fn from_key(key: Key) -> Option<Self> {
-        match key {
-            Key::KEY_ESC => Some(Button::Menu),
-            Key::KEY_POWER => Some(Button::Power),
-            Key::KEY_RIGHTCTRL => Some(Button::Select),
-            Key::KEY_ENTER => Some(Button::Start),
-            Key::KEY_E => Some(Button::L1),
-            Key::KEY_T => Some(Button::R1),
-            Key::KEY_TAB => Some(Button::L2),
-            Key::KEY_BACKSPACE => Some(Button::R2),
-            Key::KEY_VOLUMEUP => Some(Button::VolUp),
-            Key::KEY_VOLUMEDOWN => Some(Button::VolDown),
-            Key::KEY_SPACE => Some(Button::A),
-            Key::KEY_LEFTCTRL => Some(Button::B),
-            Key::KEY_LEFTSHIFT => Some(Button::X),
-            Key::KEY_LEFTALT => Some(Button::Y),
-            Key::KEY_LEFT => Some(Button::Left),
-            Key::KEY_RIGHT => Some(Button::Right),
-            Key::KEY_UP => Some(Button::Up),
-            Key::KEY_DOWN => Some(Button::Down),
-            _ => None,
-        }
+        static MIYOO: once_cell::sync::Lazy<Keymap> = once_cell::sync::Lazy::new(Keymap::miyoo);
+        MIYOO.button(key)
     }
 
     pub fn from_raw(key_code: u16) -> Option<Self> {
         Self::from_key(Key::new(key_code))
     }
+
+    /// Buttons that send `Held` events when held down, see `key_repeat`
+    pub fn repeats(&self) -> bool {
+        matches!(
+            self,
+            Self::Up
+                | Self::Down
+                | Self::Left
+                | Self::Right
+                | Self::L1
+                | Self::R1
+                | Self::L2
+                | Self::R2
+                | Self::VolUp
+                | Self::VolDown
+        )
+    }
 }
@@ -13,7 +13,7 @@ pub struct ButtonEvent {
 pub enum EventValue {
     Released = 0,
     Pressed = 1,
-    /// Currently unused
+    /// Sent repeatedly while a button is held, see `key_repeat`
     Held = 2,
 }
 
@@ -69,6 +69,12 @@ impl ButtonEvent {
         self.value == EventValue::Held
     }
 
+    /// Pressed, or repeating while held. Use this for navigation
+    #[inline(always)]
+    pub fn triggered(&self) -> bool {
+        self.pressed() || self.held()
+    }
+
     #[inline(always)]
     pub fn is_pressed(&self, target: Button) -> bool {
         self.button == target && self.pressed()
@@ -0,0 +1,279 @@
+use std::{collections::HashMap, io, path::Path, str::FromStr};
+
+use evdev::{AbsoluteAxisType, InputEvent, InputEventKind, Key};
+
+use crate::{Button, ButtonEvent, EventValue};
+
+/// Which evdev keys are which buttons
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Keymap {
+    keys: HashMap<u16, Button>,
+    /// D-pad hats are axes on most gamepads rather than keys
+    hats: bool,
+}
+
+impl Keymap {
+    /// The Miyoo's built in buttons
+    pub fn miyoo() -> Self {
+        Self::from_keys(&[
+            (Key::KEY_ESC, Button::Menu),
+            (Key::KEY_POWER, Button::Power),
+            (Key::KEY_RIGHTCTRL, Button::Select),
+            (Key::KEY_ENTER, Button::Start),
+            (Key::KEY_E, Button::L1),
+            (Key::KEY_T, Button::R1),
+            (Key::KEY_TAB, Button::L2),
+            (Key::KEY_BACKSPACE, Button::R2),
+            (Key::KEY_VOLUMEUP, Button::VolUp),
+            (Key::KEY_VOLUMEDOWN, Button::VolDown),
+            (Key::KEY_SPACE, Button::A),
+            (Key::KEY_LEFTCTRL, Button::B),
+            (Key::KEY_LEFTSHIFT, Button::X),
+            (Key::KEY_LEFTALT, Button::Y),
+            (Key::KEY_LEFT, Button::Left),
+            (Key::KEY_RIGHT, Button::Right),
+            (Key::KEY_UP, Button::Up),
+            (Key::KEY_DOWN, Button::Down),
+        ])
+    }
+
+    /// A desktop keyboard, along with any gamepad
+    pub fn keyboard() -> Self {
+        let mut keymap = Self::from_keys(&[
+            (Key::KEY_UP, Button::Up),
+            (Key::KEY_DOWN, Button::Down),
+            (Key::KEY_LEFT, Button::Left),
+            (Key::KEY_RIGHT, Button::Right),
+            (Key::KEY_X, Button::A),
+            (Key::KEY_Z, Button::B),
+            (Key::KEY_S, Button::X),
+            (Key::KEY_A, Button::Y),
+            (Key::KEY_Q, Button::L1),
+            (Key::KEY_W, Button::R1),
+            (Key::KEY_1, Button::L2),
+            (Key::KEY_2, Button::R2),
+            (Key::KEY_ENTER, Button::Start),
+            (Key::KEY_RIGHTSHIFT, Button::Select),
+            (Key::KEY_ESC, Button::Menu),
+            (Key::KEY_P, Button::Power),
+            (Key::KEY_EQUAL, Button::VolUp),
+            (Key::KEY_MINUS, Button::VolDown),
+        ]);
+        keymap.keys.extend(Self::gamepad().keys);
+        keymap.hats = true;
+        keymap
+    }
+
+    /// Standard evdev gamepad buttons, like from usb or bluetooth controllers
+    pub fn gamepad() -> Self {
+        let mut keymap = Self::from_keys(&[
+            (Key::BTN_DPAD_UP, Button::Up),
+            (Key::BTN_DPAD_DOWN, Button::Down),
+            (Key::BTN_DPAD_LEFT, Button::Left),
+            (Key::BTN_DPAD_RIGHT, Button::Right),
+            // Nintendo layout, like the Miyoo
+            (Key::BTN_EAST, Button::A),
+            (Key::BTN_SOUTH, Button::B),
+            (Key::BTN_NORTH, Button::X),
+            (Key::BTN_WEST, Button::Y),
+            (Key::BTN_TL, Button::L1),
+            (Key::BTN_TR, Button::R1),
+            (Key::BTN_TL2, Button::L2),
+            (Key::BTN_TR2, Button::R2),
+            (Key::BTN_START, Button::Start),
+            (Key::BTN_SELECT, Button::Select),
+            (Key::BTN_MODE, Button::Menu),
+        ]);
+        keymap.hats = true;
+        keymap
+    }
+
+    /// Reads a json object of evdev key names to buttons, like `{ "KEY_X": "A" }`
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let names: HashMap<String, Button> = serde_json::from_slice(&bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let mut keymap = Self {
+            keys: HashMap::new(),
+            hats: true,
+        };
+        for (name, button) in names {
+            let key = Key::from_str(&name).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("Unknown key {name}"))
+            })?;
+            keymap.keys.insert(key.code(), button);
+        }
+        Ok(keymap)
+    }
+
+    fn from_keys(keys: &[(Key, Button)]) -> Self {
+        Self {
+            keys: keys
+                .iter()
+                .map(|(key, button)| (key.code(), *button))
+                .collect(),
+            hats: false,
+        }
+    }
+
+    pub fn button(&self, key: Key) -> Option<Button> {
+        self.keys.get(&key.code()).copied()
+    }
+
+    /// If a device has any key in this keymap
+    pub fn matches(&self, keys: impl IntoIterator<Item = Key>) -> bool {
+        keys.into_iter()
+            .any(|key| self.keys.contains_key(&key.code()))
+    }
+
+    /// Button events for an evdev event, hats release the opposite direction along with any press
+    pub fn events(&self, event: InputEvent) -> Vec<ButtonEvent> {
+        match event.kind() {
+            InputEventKind::Key(key) => {
+                let Some(button) = self.button(key) else {
+                    return Vec::new();
+                };
+                let value = match event.value() {
+                    0 => EventValue::Released,
+                    1 => EventValue::Pressed,
+                    _ => return Vec::new(),
+                };
+                vec![ButtonEvent::new(button, value)]
+            }
+            InputEventKind::AbsAxis(axis) if self.hats => {
+                let (negative, positive) = match axis {
+                    AbsoluteAxisType::ABS_HAT0X => (Button::Left, Button::Right),
+                    AbsoluteAxisType::ABS_HAT0Y => (Button::Up, Button::Down),
+                    _ => return Vec::new(),
+                };
+                // Hats can go straight from one side to the other without centering
+                match event.value() {
+                    value if value < 0 => vec![
+                        ButtonEvent::new(positive, EventValue::Released),
+                        ButtonEvent::new(negative, EventValue::Pressed),
+                    ],
+                    value if value > 0 => vec![
+                        ButtonEvent::new(negative, EventValue::Released),
+                        ButtonEvent::new(positive, EventValue::Pressed),
+                    ],
+                    _ => vec![
+                        ButtonEvent::new(negative, EventValue::Released),
+                        ButtonEvent::new(positive, EventValue::Released),
+                    ],
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use evdev::EventType;
+
+    use super::*;
+
+    fn hat(axis: AbsoluteAxisType, value: i32) -> InputEvent {
+        InputEvent::new(EventType::ABSOLUTE, axis.0, value)
+    }
+
+    #[test]
+    fn keys() {
+        let keymap = Keymap::keyboard();
+        assert_eq!(
+            keymap.events(InputEvent::new(EventType::KEY, Key::KEY_X.code(), 1)),
+            vec![ButtonEvent::new(Button::A, EventValue::Pressed)]
+        );
+        assert_eq!(
+            keymap.events(InputEvent::new(EventType::KEY, Key::KEY_X.code(), 0)),
+            vec![ButtonEvent::new(Button::A, EventValue::Released)]
+        );
+        // Autorepeat from the kernel, `key_repeat` does this instead
+        assert!(keymap
+            .events(InputEvent::new(EventType::KEY, Key::KEY_X.code(), 2))
+            .is_empty());
+        assert!(keymap
+            .events(InputEvent::new(EventType::KEY, Key::KEY_F1.code(), 1))
+            .is_empty());
+    }
+
+    #[test]
+    fn hat_flips() {
+        let keymap = Keymap::gamepad();
+        assert_eq!(
+            keymap.events(hat(AbsoluteAxisType::ABS_HAT0X, -1)),
+            vec![
+                ButtonEvent::new(Button::Right, EventValue::Released),
+                ButtonEvent::new(Button::Left, EventValue::Pressed),
+            ]
+        );
+        // Straight to the other side without centering first
+        assert_eq!(
+            keymap.events(hat(AbsoluteAxisType::ABS_HAT0X, 1)),
+            vec![
+                ButtonEvent::new(Button::Left, EventValue::Released),
+                ButtonEvent::new(Button::Right, EventValue::Pressed),
+            ]
+        );
+        assert_eq!(
+            keymap.events(hat(AbsoluteAxisType::ABS_HAT0Y, -1)),
+            vec![
+                ButtonEvent::new(Button::Down, EventValue::Released),
+                ButtonEvent::new(Button::Up, EventValue::Pressed),
+            ]
+        );
+    }
+
+    #[test]
+    fn hat_centers() {
+        let keymap = Keymap::gamepad();
+        assert_eq!(
+            keymap.events(hat(AbsoluteAxisType::ABS_HAT0Y, 0)),
+            vec![
+                ButtonEvent::new(Button::Up, EventValue::Released),
+                ButtonEvent::new(Button::Down, EventValue::Released),
+            ]
+        );
+        // Other axes, like sticks, aren't buttons
+        assert!(keymap.events(hat(AbsoluteAxisType::ABS_X, 1)).is_empty());
+        // Neither are hats without a keymap that wants them
+        assert!(Keymap::miyoo()
+            .events(hat(AbsoluteAxisType::ABS_HAT0X, 1))
+            .is_empty());
+    }
+
+    #[test]
+    fn load() {
+        let dir = std::env::temp_dir().join(format!("input-{}-keymap", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("keymap.json");
+        std::fs::write(&path, r#"{ "KEY_K": "A", "BTN_SOUTH": "B" }"#).unwrap();
+        let keymap = Keymap::load(&path).unwrap();
+        assert_eq!(keymap.button(Key::KEY_K), Some(Button::A));
+        assert_eq!(keymap.button(Key::BTN_SOUTH), Some(Button::B));
+        assert_eq!(keymap.button(Key::KEY_X), None);
+        // Loaded keymaps are for gamepads too
+        assert!(keymap.hats);
+
+        let unknown = dir.join("unknown.json");
+        std::fs::write(&unknown, r#"{ "KEY_NOPE": "A" }"#).unwrap();
+        assert_eq!(
+            Keymap::load(&unknown).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let bad_button = dir.join("bad_button.json");
+        std::fs::write(&bad_button, r#"{ "KEY_K": "Turbo" }"#).unwrap();
+        assert_eq!(
+            Keymap::load(&bad_button).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        assert_eq!(
+            Keymap::load(dir.join("missing.json")).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
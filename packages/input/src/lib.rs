@@ -1,14 +1,18 @@
 pub mod button;
 pub mod event;
+pub mod keymap;
+pub mod repeat;
+pub mod source;
 
 use std::{io, process, time::Instant};
 
-use evdev::Device;
-use futures_util::StreamExt;
 use tokio::sync::{mpsc, oneshot};
 
 pub use button::Button;
 pub use event::{ButtonEvent, EventValue};
+pub use keymap::Keymap;
+pub use repeat::{key_repeat, Repeat};
+pub use source::Source;
 
 #[derive(Debug)]
 pub struct ButtonHandler {
@@ -19,36 +23,22 @@ pub struct ButtonHandler {
 
 impl ButtonHandler {}
 
+/// Starts reading input from the detected source, see `Source::detect`
 pub async fn input_task() -> io::Result<(
     mpsc::Receiver<ButtonEvent>,
     impl std::future::Future<Output = ()>,
 )> {
-    let mut stream = asyncify(|| {
-        let device = Device::open("/dev/input/event0")?;
-        device.into_event_stream()
-    })
-    .await?;
-    let (sender, receiver) = mpsc::channel(64);
+    input_task_from(Source::detect()).await
+}
 
-    let future = async move {
-        loop {
-            match stream.next().await {
-                Some(res) => match res {
-                    Ok(event) => {
-                        if let Some(event) = ButtonEvent::from_event(event) {
-                            sender.send(event).await.ok();
-                        }
-                    }
-                    Err(err) => {
-                        panic!("Input event stream error: {}", err);
-                    }
-                },
-                None => {
-                    panic!("Input event stream stopped unexpectedly.");
-                }
-            }
-        }
-    };
+pub async fn input_task_from(
+    source: Source,
+) -> io::Result<(
+    mpsc::Receiver<ButtonEvent>,
+    impl std::future::Future<Output = ()>,
+)> {
+    let (sender, receiver) = mpsc::channel(64);
+    let future = source.start(sender).await?;
 
     Ok((receiver, future))
 }
@@ -67,3 +57,32 @@ where
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn virtual_source() {
+        let (sender, source) = Source::virtual_source();
+        let (mut receiver, task) = input_task_from(source).await.unwrap();
+        let task = tokio::spawn(task);
+
+        let events = [
+            ButtonEvent::new(Button::A, EventValue::Pressed),
+            ButtonEvent::new(Button::Up, EventValue::Pressed),
+            ButtonEvent::new(Button::A, EventValue::Released),
+        ];
+        for event in events.clone() {
+            sender.send(event).await.unwrap();
+        }
+        for event in events {
+            assert_eq!(receiver.recv().await, Some(event));
+        }
+
+        // Stops once nothing can be sent anymore
+        drop(sender);
+        task.await.unwrap();
+        assert_eq!(receiver.recv().await, None);
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use tokio::{select, sync::mpsc, time::Instant};
+
+use crate::{Button, ButtonEvent, EventValue};
+
+/// Timing for repeated `Held` events while a button is held down
+#[derive(Debug, Clone, Copy)]
+pub struct Repeat {
+    /// Wait before the first repeat
+    pub delay: Duration,
+    /// Time between the first few repeats
+    pub interval: Duration,
+    /// Repeats never get faster than this
+    pub min_interval: Duration,
+    /// Interval is multiplied by this after every repeat
+    pub acceleration: f32,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(400),
+            interval: Duration::from_millis(120),
+            min_interval: Duration::from_millis(30),
+            acceleration: 0.85,
+        }
+    }
+}
+
+struct HeldButton {
+    button: Button,
+    next_at: Instant,
+    interval: Duration,
+}
+
+/// Passes events from `recv` through, adding `Held` events while a repeating button is held.
+/// Only the last pressed button repeats, like a keyboard
+pub fn key_repeat(
+    mut recv: mpsc::Receiver<ButtonEvent>,
+    repeat: Repeat,
+) -> (
+    mpsc::Receiver<ButtonEvent>,
+    impl std::future::Future<Output = ()>,
+) {
+    let (sender, receiver) = mpsc::channel(64);
+
+    let future = async move {
+        let mut held: Option<HeldButton> = None;
+
+        loop {
+            let next_at = held.as_ref().map(|held| held.next_at);
+
+            select! {
+                event = recv.recv() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+
+                    match event.value() {
+                        EventValue::Pressed if event.button().repeats() => {
+                            held = Some(HeldButton {
+                                button: *event.button(),
+                                next_at: Instant::now() + repeat.delay,
+                                interval: repeat.interval,
+                            });
+                        }
+                        EventValue::Released
+                            if held.as_ref().map_or(false, |held| held.button == *event.button()) =>
+                        {
+                            held = None;
+                        }
+                        _ => {}
+                    }
+
+                    if sender.send(event).await.is_err() {
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep_until(next_at.unwrap_or_else(Instant::now)), if next_at.is_some() => {
+                    if let Some(held) = held.as_mut() {
+                        held.next_at = Instant::now() + held.interval;
+                        held.interval = held
+                            .interval
+                            .mul_f32(repeat.acceleration)
+                            .max(repeat.min_interval);
+
+                        if sender
+                            .send(ButtonEvent::new(held.button, EventValue::Held))
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    (receiver, future)
+}
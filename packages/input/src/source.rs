@@ -0,0 +1,245 @@
+use std::{
+    collections::HashSet,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use evdev::Device;
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use tokio::{select, sync::mpsc};
+
+use crate::{asyncify, ButtonEvent, Keymap};
+
+/// Env var to pick the input source, `miyoo` or `evdev`
+pub const INPUT_ENV: &str = "OXIDE_INPUT";
+/// Env var with the path to a keymap for the `evdev` source, see `Keymap::load`
+pub const KEYMAP_ENV: &str = "OXIDE_KEYMAP";
+
+const MIYOO_DEVICE: &str = "/dev/input/event0";
+const INPUT_DIR: &str = "/dev/input";
+/// Only exists on the Miyoo's firmware
+const MIYOO_VENDOR_DIR: &str = "/customer/app";
+/// How often to check for devices plugged in after starting, devices are only opened
+/// and enumerated when something new shows up
+const HOTPLUG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Where button events come from
+#[derive(Debug)]
+pub enum Source {
+    /// The built in buttons, plus any gamepads plugged in
+    Miyoo,
+    /// Every evdev keyboard or gamepad with a key in the keymap
+    Evdev(Keymap),
+    /// Events sent from somewhere else, like tests
+    Virtual(mpsc::Receiver<ButtonEvent>),
+}
+
+type Forward = Pin<Box<dyn Future<Output = ()> + Send>>;
+/// Paths of the devices being forwarded, so they aren't opened twice
+type Open = Arc<Mutex<HashSet<PathBuf>>>;
+
+impl Source {
+    /// Picks from `INPUT_ENV`, or `Miyoo` when running on one and `Evdev` otherwise
+    pub fn detect() -> Self {
+        let miyoo = match std::env::var(INPUT_ENV).as_deref() {
+            Ok("miyoo") => true,
+            Ok("evdev") => false,
+            _ => Path::new(MIYOO_VENDOR_DIR).exists(),
+        };
+
+        if miyoo {
+            return Self::Miyoo;
+        }
+
+        let keymap = match std::env::var(KEYMAP_ENV) {
+            Ok(path) => Keymap::load(&path).unwrap_or_else(|err| {
+                tracing::error!("Failed to load keymap {path}: {err:?}");
+                Keymap::keyboard()
+            }),
+            Err(_) => Keymap::keyboard(),
+        };
+        Self::Evdev(keymap)
+    }
+
+    /// Source to send events into yourself
+    pub fn virtual_source() -> (mpsc::Sender<ButtonEvent>, Self) {
+        let (sender, receiver) = mpsc::channel(64);
+        (sender, Self::Virtual(receiver))
+    }
+
+    /// Opens the devices and returns a future forwarding their events to `sender`
+    pub(crate) async fn start(self, sender: mpsc::Sender<ButtonEvent>) -> io::Result<Forward> {
+        match self {
+            Self::Miyoo => {
+                let open = Open::default();
+                let builtin = asyncify(|| Device::open(MIYOO_DEVICE)).await?;
+                let mut forwards = vec![forward(
+                    MIYOO_DEVICE.into(),
+                    builtin,
+                    Arc::new(Keymap::miyoo()),
+                    sender.clone(),
+                    true,
+                    open.clone(),
+                )?];
+
+                // Extra controllers are nice to have, but not needed
+                let gamepad = Arc::new(Keymap::gamepad());
+                forwards.extend(open_devices(&gamepad, &sender, &open).await);
+
+                Ok(Box::pin(hotplug(forwards, gamepad, sender, open)))
+            }
+            Self::Evdev(keymap) => {
+                let keymap = Arc::new(keymap);
+                let open = Open::default();
+                let forwards = open_devices(&keymap, &sender, &open).await;
+
+                if forwards.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "No keyboard or gamepad found",
+                    ));
+                }
+
+                Ok(Box::pin(hotplug(forwards, keymap, sender, open)))
+            }
+            Self::Virtual(mut receiver) => Ok(Box::pin(async move {
+                while let Some(event) = receiver.recv().await {
+                    if sender.send(event).await.is_err() {
+                        break;
+                    }
+                }
+            })),
+        }
+    }
+}
+
+/// Runs `forwards`, adding devices with any key from `keymap` as they're plugged in
+async fn hotplug(
+    forwards: Vec<Forward>,
+    keymap: Arc<Keymap>,
+    sender: mpsc::Sender<ButtonEvent>,
+    open: Open,
+) {
+    let mut forwards: FuturesUnordered<Forward> = forwards.into_iter().collect();
+    let mut interval = tokio::time::interval(HOTPLUG_INTERVAL);
+    // Everything there now was just enumerated
+    interval.reset();
+    let mut present = input_paths().await;
+
+    loop {
+        select! {
+            Some(()) = forwards.next() => {}
+            _ = interval.tick() => {
+                if sender.is_closed() {
+                    break;
+                }
+                // Listing the dir is cheap, opening every device to enumerate them isn't
+                let now = input_paths().await;
+                if now.difference(&present).next().is_some() {
+                    forwards.extend(open_devices(&keymap, &sender, &open).await);
+                }
+                present = now;
+            }
+        }
+    }
+}
+
+/// Entries in the input dir, which gains one when a device is plugged in
+async fn input_paths() -> HashSet<PathBuf> {
+    asyncify(|| {
+        std::fs::read_dir(INPUT_DIR)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Starts forwarding every device with any key from `keymap` that isn't already open.
+/// Devices that fail to open are skipped
+async fn open_devices(
+    keymap: &Arc<Keymap>,
+    sender: &mpsc::Sender<ButtonEvent>,
+    open: &Open,
+) -> Vec<Forward> {
+    let mut forwards = Vec::new();
+    for (path, device) in devices(keymap, open).await {
+        match forward(
+            path,
+            device,
+            keymap.clone(),
+            sender.clone(),
+            false,
+            open.clone(),
+        ) {
+            Ok(future) => forwards.push(future),
+            Err(err) => tracing::error!("Failed to open input device: {err:?}"),
+        }
+    }
+    forwards
+}
+
+/// Evdev devices with any key from `keymap` that aren't already open
+async fn devices(keymap: &Arc<Keymap>, open: &Open) -> Vec<(PathBuf, Device)> {
+    let keymap = keymap.clone();
+    let open = open.clone();
+    asyncify(move || {
+        Ok(evdev::enumerate()
+            .filter(|(path, _)| !open.lock().unwrap().contains(path))
+            .filter(|(_, device)| {
+                device
+                    .supported_keys()
+                    .map_or(false, |keys| keymap.matches(keys.iter()))
+            })
+            .inspect(|(path, device)| {
+                tracing::debug!(
+                    "Using input device {}: {}",
+                    path.display(),
+                    device.name().unwrap_or("unnamed")
+                );
+            })
+            .collect())
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Sends a device's events until it goes away. Only `required` devices going away is fatal
+fn forward(
+    path: PathBuf,
+    device: Device,
+    keymap: Arc<Keymap>,
+    sender: mpsc::Sender<ButtonEvent>,
+    required: bool,
+    open: Open,
+) -> io::Result<Forward> {
+    // Even if this fails, otherwise it's retried on every hotplug check
+    open.lock().unwrap().insert(path.clone());
+    let mut stream = device.into_event_stream()?;
+
+    Ok(Box::pin(async move {
+        loop {
+            match stream.next().await {
+                Some(Ok(event)) => {
+                    for event in keymap.events(event) {
+                        sender.send(event).await.ok();
+                    }
+                }
+                Some(Err(err)) if required => panic!("Input event stream error: {}", err),
+                None if required => panic!("Input event stream stopped unexpectedly."),
+                Some(Err(err)) => {
+                    tracing::warn!("Input device went away: {err}");
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        // Can be opened again if it comes back
+        open.lock().unwrap().remove(&path);
+    }))
+}
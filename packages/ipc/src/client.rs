@@ -1,18 +1,70 @@
-use http::{Method, Request, Response};
+use std::time::Duration;
+
+use http::{Method, Request, StatusCode};
 use hyper::Body;
 use hyperlocal::{UnixClientExt, Uri};
 use once_cell::sync::Lazy;
 
-use crate::{functions::Function, SOCKET_PATH};
+use crate::{functions::Function, Error, ErrorCode};
 
 static CLIENT: Lazy<hyper::Client<hyperlocal::UnixConnector>> = Lazy::new(|| hyper::Client::unix());
 
-pub async fn call<F: Function>(args: F::ReqBody) -> Result<Response<Body>, hyper::Error> {
-    let req = Request::builder()
-        .uri(Uri::new(SOCKET_PATH, F::path()))
-        .method(Method::POST)
-        .header("Content-Type", "application/json")
-        .body(hyper::body::Body::from(serde_json::to_vec(&args).unwrap()))
-        .unwrap();
-    CLIENT.request(req).await
+/// Each attempt at a call gives up after this long
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+/// Times a call is retried if it couldn't connect, like when the socket isn't bound yet
+pub const RETRIES: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_millis(200);
+
+pub async fn call<F: Function>(args: F::ReqBody) -> Result<F::ResBody, Error> {
+    let body = serde_json::to_vec(&args).unwrap();
+
+    let mut attempt = 0;
+    let res = loop {
+        let req = Request::builder()
+            .uri(Uri::new(F::socket(), F::path()))
+            .method(Method::POST)
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.clone()))
+            .unwrap();
+
+        match tokio::time::timeout(TIMEOUT, CLIENT.request(req)).await {
+            Ok(Ok(res)) => break res,
+            // Nothing was sent, so trying again can't run it twice
+            Ok(Err(err)) if err.is_connect() && attempt < RETRIES => {
+                attempt += 1;
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+            Ok(Err(err)) if err.is_connect() => {
+                return Err(Error::new(ErrorCode::Unavailable, err.to_string()))
+            }
+            Ok(Err(err)) => return Err(Error::new(ErrorCode::Failed, err.to_string())),
+            Err(_) => {
+                return Err(Error::new(
+                    ErrorCode::TimedOut,
+                    format!("{} took over {TIMEOUT:?}", F::path()),
+                ))
+            }
+        }
+    };
+
+    let status = res.status();
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .map_err(|err| Error::new(ErrorCode::Failed, err.to_string()))?;
+
+    if status.is_success() {
+        return serde_json::from_slice(&body)
+            .map_err(|err| Error::new(ErrorCode::BadResponse, err.to_string()));
+    }
+
+    // Errors from handlers carry their own code, anything else is from axum itself
+    Err(
+        serde_json::from_slice(&body).unwrap_or_else(|_| match status {
+            StatusCode::NOT_FOUND => Error::new(ErrorCode::NotFound, format!("No {}", F::path())),
+            status => Error::new(
+                ErrorCode::Failed,
+                format!("{} failed with {status}", F::path()),
+            ),
+        }),
+    )
 }
@@ -0,0 +1,73 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+/// Error from a call, either sent back by the handler or hit making the call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[error("{code:?}: {message}")]
+pub struct Error {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// The handler ran but failed
+    Failed,
+    /// The request body didn't match the function's `ReqBody`
+    BadRequest,
+    /// Nothing is served at the function's path, likely from a different build
+    NotFound,
+    /// Couldn't connect, even after retrying
+    Unavailable,
+    TimedOut,
+    /// The response didn't match the function's `ResBody`
+    BadResponse,
+}
+
+impl Error {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// What's sent back for each code
+    pub fn status(&self) -> http::StatusCode {
+        match self.code {
+            ErrorCode::Failed => http::StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::BadRequest => http::StatusCode::BAD_REQUEST,
+            ErrorCode::NotFound => http::StatusCode::NOT_FOUND,
+            ErrorCode::Unavailable => http::StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::TimedOut => http::StatusCode::GATEWAY_TIMEOUT,
+            ErrorCode::BadResponse => http::StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::new(ErrorCode::Failed, err.to_string())
+    }
+}
+
+/// For callers that report everything as `io::Error`
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err.code {
+            ErrorCode::Unavailable => io::ErrorKind::NotConnected,
+            ErrorCode::TimedOut => io::ErrorKind::TimedOut,
+            ErrorCode::BadRequest | ErrorCode::BadResponse => io::ErrorKind::InvalidData,
+            ErrorCode::Failed | ErrorCode::NotFound => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+#[cfg(feature = "server")]
+impl axum::response::IntoResponse for Error {
+    fn into_response(self) -> axum::response::Response {
+        (self.status(), axum::Json(self)).into_response()
+    }
+}
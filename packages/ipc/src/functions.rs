@@ -1,11 +1,52 @@
+use std::path::PathBuf;
+
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use crate::{socket_path, system_socket_path};
+
+/// Bump whenever a function is added, removed or changes its path or bodies, see `Hello`
+pub const PROTOCOL_VERSION: u32 = 1;
+
 pub trait Function {
-    type ReqBody: Serialize + DeserializeOwned;
+    type ReqBody: Serialize + DeserializeOwned + Send + 'static;
 
-    type ResBody: Serialize + DeserializeOwned;
+    type ResBody: Serialize + DeserializeOwned + Send + 'static;
 
     fn path() -> &'static str;
+
+    /// Socket of the process serving this, the emulator unless overridden
+    fn socket() -> PathBuf {
+        socket_path()
+    }
+}
+
+/// Asks the emulator what it supports, so a mismatched build is caught right after starting.
+/// This one can never change
+pub struct Hello;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HelloArgs {
+    /// The caller's `PROTOCOL_VERSION`
+    pub version: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// The emulator's `PROTOCOL_VERSION`
+    pub version: u32,
+    /// Paths of every function served
+    pub functions: Vec<String>,
+    /// Package version of the emulator, for error messages
+    pub build: String,
+}
+
+impl Function for Hello {
+    type ReqBody = HelloArgs;
+    type ResBody = Capabilities;
+
+    fn path() -> &'static str {
+        "/hello"
+    }
 }
 
 pub struct SaveState;
@@ -24,6 +65,21 @@ impl Function for SaveState {
     }
 }
 
+/// Writes the game's save ram to disk
+pub struct FlushSram;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlushSramArgs {}
+
+impl Function for FlushSram {
+    type ReqBody = FlushSramArgs;
+    type ResBody = ();
+
+    fn path() -> &'static str {
+        "/flush-sram"
+    }
+}
+
 pub struct Stop;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,3 +107,102 @@ impl Function for Start {
         "/start"
     }
 }
+
+pub struct ShowOverlay;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverlayKind {
+    Volume,
+    Brightness,
+    /// Shown when the battery gets low
+    Battery,
+}
+
+/// Level bar shown briefly on top of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Overlay {
+    pub kind: OverlayKind,
+    pub level: u8,
+    pub max: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShowOverlayArgs {
+    pub overlay: Overlay,
+}
+
+impl Function for ShowOverlay {
+    type ReqBody = ShowOverlayArgs;
+    type ResBody = ();
+
+    fn path() -> &'static str {
+        "/show-overlay"
+    }
+}
+
+/// Served by the os, writes the emulator's forwarded log lines to the os's log
+pub struct Log;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogArgs {
+    /// Already formatted, each ending in a newline
+    pub lines: Vec<String>,
+}
+
+impl Function for Log {
+    type ReqBody = LogArgs;
+    type ResBody = ();
+
+    fn path() -> &'static str {
+        "/log"
+    }
+
+    fn socket() -> PathBuf {
+        system_socket_path()
+    }
+}
+
+/// Things the emulator tells the os about without being asked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event {
+    /// A save state was written, `None` being the auto slot
+    Saved {
+        slot: Option<usize>,
+    },
+    SaveFailed {
+        slot: Option<usize>,
+        error: String,
+    },
+    /// Save ram was written to disk
+    SramFlushed,
+    /// The core asked to exit, like from a game's quit option
+    ShutdownRequested,
+    /// Frame rate has been below the core's for a while
+    FpsDropped {
+        fps: u32,
+        target: u32,
+    },
+    /// Frame rate is back up after dropping
+    FpsRecovered,
+}
+
+/// Served by the os, sends an `Event` from the emulator
+pub struct SendEvent;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SendEventArgs {
+    pub event: Event,
+}
+
+impl Function for SendEvent {
+    type ReqBody = SendEventArgs;
+    type ResBody = ();
+
+    fn path() -> &'static str {
+        "/event"
+    }
+
+    fn socket() -> PathBuf {
+        system_socket_path()
+    }
+}
@@ -4,10 +4,23 @@ pub mod server;
 #[cfg(feature = "server")]
 pub use axum::{extract, http::*, routing, Router};
 #[cfg(feature = "server")]
-pub use hyper::Error;
+pub use hyper::Error as ServerError;
+#[cfg(feature = "server")]
+pub use server::{Functions, RouterExt};
 
 #[cfg(feature = "client")]
 pub mod client;
+mod error;
 pub mod functions;
 
-pub const SOCKET_PATH: &str = "/tmp/ipc.sock";
+pub use error::{Error, ErrorCode};
+
+/// Where the emulator serves ipc, see `layout::Layout::socket`
+pub fn socket_path() -> std::path::PathBuf {
+    layout::layout().socket()
+}
+
+/// Where the os serves ipc, see `layout::Layout::system_socket`
+pub fn system_socket_path() -> std::path::PathBuf {
+    layout::layout().system_socket()
+}
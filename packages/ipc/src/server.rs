@@ -1,14 +1,105 @@
-use std::future::Future;
+use std::{future::Future, path::Path};
 
-use axum::Router;
+use axum::{extract::rejection::JsonRejection, routing::post, Json, Router};
 use hyper::Server;
 use hyperlocal::UnixServerExt;
 
-use crate::SOCKET_PATH;
+use crate::{functions::Function, socket_path, Error, ErrorCode};
 
 /// Returns a future which drives the ipc server
 pub fn server(router: Router) -> impl Future<Output = Result<(), hyper::Error>> {
-    Server::bind_unix(SOCKET_PATH)
+    server_at(socket_path(), router)
+}
+
+/// Like `server`, but on any socket
+pub fn server_at(
+    socket: impl AsRef<Path>,
+    router: Router,
+) -> impl Future<Output = Result<(), hyper::Error>> {
+    Server::bind_unix(socket)
         .unwrap()
         .serve(router.into_make_service())
 }
+
+pub trait RouterExt {
+    /// Serves `function` at its path, with the handler taking its `ReqBody` and returning its
+    /// `ResBody`. Bad request bodies are sent back as `ErrorCode::BadRequest`
+    fn function<F, H, Fut>(self, function: F, handler: H) -> Self
+    where
+        F: Function,
+        H: FnOnce(F::ReqBody) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Result<F::ResBody, Error>> + Send;
+}
+
+impl<S> RouterExt for Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn function<F, H, Fut>(self, _function: F, handler: H) -> Self
+    where
+        F: Function,
+        H: FnOnce(F::ReqBody) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Result<F::ResBody, Error>> + Send,
+    {
+        self.route(
+            F::path(),
+            post(|body: Result<Json<F::ReqBody>, JsonRejection>| async move {
+                let Json(args) =
+                    body.map_err(|err| Error::new(ErrorCode::BadRequest, err.body_text()))?;
+                handler(args).await.map(Json)
+            }),
+        )
+    }
+}
+
+/// A router that remembers which functions were added, so `Hello` can answer with exactly
+/// what it serves
+pub struct Functions<S = ()> {
+    router: Router<S>,
+    paths: Vec<&'static str>,
+}
+
+impl<S> Functions<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            router: Router::new(),
+            paths: Vec::new(),
+        }
+    }
+
+    /// See `RouterExt::function`
+    pub fn function<F, H, Fut>(self, function: F, handler: H) -> Self
+    where
+        F: Function,
+        H: FnOnce(F::ReqBody) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Result<F::ResBody, Error>> + Send,
+    {
+        let mut paths = self.paths;
+        paths.push(F::path());
+        Self {
+            router: self.router.function(function, handler),
+            paths,
+        }
+    }
+
+    /// Paths of the functions added so far
+    pub fn paths(&self) -> &[&'static str] {
+        &self.paths
+    }
+
+    pub fn into_router(self) -> Router<S> {
+        self.router
+    }
+}
+
+impl<S> Default for Functions<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
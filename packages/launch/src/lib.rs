@@ -3,12 +3,22 @@ pub mod error;
 use std::{io, path::Path};
 
 pub use error::Error;
-use miyoo_mini_hal::charger;
+use layout::layout;
+use miyoo_mini_hal::{hal, Backend};
 use tokio::task::spawn_blocking;
 
 pub async fn launch() -> Result<(), Error> {
+    hal().init().await?;
+    // Settings and such go here, it may not exist under a custom root
+    tokio::fs::create_dir_all(layout().app()).await?;
+
+    // There's no sd card to set up off device
+    if hal().is_simulated() {
+        return Ok(());
+    }
+
     // Copy .tmp_update from app dir to root of sd card
-    match copy_recursively("./.tmp_update", "/mnt/SDCARD/.tmp_update").await {
+    match copy_recursively("./.tmp_update", layout().tmp_update()).await {
         Ok(_) => {}
         Err(err) => match err.kind() {
             io::ErrorKind::AlreadyExists => {}
@@ -16,8 +26,6 @@ pub async fn launch() -> Result<(), Error> {
         },
     };
 
-    charger::init_charger_detection().await.unwrap();
-
     Ok(())
 }
 
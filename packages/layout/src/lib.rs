@@ -0,0 +1,131 @@
+//! Where everything lives on the sd card. Paths all come from a root, which is the sd card
+//! by default and can be moved with `OXIDE_ROOT` or `--root` to run somewhere else
+
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::OnceCell;
+
+static LAYOUT: OnceCell<Layout> = OnceCell::new();
+
+/// Env var to use a different root
+pub const ROOT_ENV: &str = "OXIDE_ROOT";
+/// Cli flag to use a different root, wins over the env var
+pub const ROOT_FLAG: &str = "--root";
+pub const DEFAULT_ROOT: &str = "/mnt/SDCARD";
+/// Env var to put sockets and other temporary files somewhere other than `/tmp`,
+/// like when running more than one copy on a desktop
+pub const TMP_ENV: &str = "OXIDE_TMP";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    root: PathBuf,
+    /// The sd card can't hold sockets, so this is outside of root
+    tmp: PathBuf,
+}
+
+/// Sets the root from the cli, must be called before anything uses `layout`
+pub fn init(root: Option<PathBuf>) -> &'static Layout {
+    if let Some(root) = root {
+        if LAYOUT.set(Layout::new(root)).is_err() {
+            tracing::error!("Layout was used before it was initialized");
+        }
+    }
+    layout()
+}
+
+/// Sets the root from `ROOT_FLAG` in the process args, if it's there
+pub fn init_from_args() -> &'static Layout {
+    let root = std::env::args()
+        .skip_while(|arg| arg != ROOT_FLAG)
+        .nth(1)
+        .map(PathBuf::from);
+    init(root)
+}
+
+pub fn layout() -> &'static Layout {
+    LAYOUT.get_or_init(|| match std::env::var_os(ROOT_ENV) {
+        Some(root) => Layout::new(root.into()),
+        None => Layout::new(DEFAULT_ROOT.into()),
+    })
+}
+
+impl Layout {
+    pub fn new(root: PathBuf) -> Self {
+        let tmp = std::env::var_os(TMP_ENV).map_or_else(|| PathBuf::from("/tmp"), PathBuf::from);
+        Self { root, tmp }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Roms for a console, by the console's name
+    pub fn games(&self, console: &str) -> PathBuf {
+        self.root.join("Games").join(console)
+    }
+
+    pub fn cores(&self) -> PathBuf {
+        self.root.join("Cores")
+    }
+
+    pub fn core(&self, core: &str) -> PathBuf {
+        self.cores().join(format!("{core}_libretro.so"))
+    }
+
+    /// BIOS files, shared by every core as their system dir
+    pub fn bios(&self) -> PathBuf {
+        self.root.join("BIOS")
+    }
+
+    /// Holds a dir per core. Before the BIOS dir, `Saves/<core>` was the core's system and save dir
+    pub fn all_saves(&self) -> PathBuf {
+        self.root.join("Saves")
+    }
+
+    /// Save states and save ram for a core, also given to the core as its save dir
+    pub fn saves(&self, core: &str) -> PathBuf {
+        self.all_saves().join(core).join("saves")
+    }
+
+    /// Box art for a console
+    pub fn imgs(&self, console: &str) -> PathBuf {
+        self.root.join("Imgs").join(console)
+    }
+
+    pub fn dat(&self, console: &str) -> PathBuf {
+        self.root.join("Dats").join(format!("{console}.dat"))
+    }
+
+    /// Where the os is installed, also used as home
+    pub fn app(&self) -> PathBuf {
+        self.root.join("miyoo").join("app")
+    }
+
+    /// Settings, history and other files the os keeps
+    pub fn data(&self, file: &str) -> PathBuf {
+        self.app().join(file)
+    }
+
+    pub fn logs(&self) -> PathBuf {
+        self.app()
+    }
+
+    /// Copied to the root on launch so the firmware starts us
+    pub fn tmp_update(&self) -> PathBuf {
+        self.root.join(".tmp_update")
+    }
+
+    pub fn tmp(&self) -> &Path {
+        &self.tmp
+    }
+
+    /// Socket the emulator serves ipc on
+    pub fn socket(&self) -> PathBuf {
+        self.tmp.join("ipc.sock")
+    }
+
+    /// Socket the os serves ipc on, for the emulator to call back
+    pub fn system_socket(&self) -> PathBuf {
+        self.tmp.join("system.sock")
+    }
+}
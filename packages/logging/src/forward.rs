@@ -0,0 +1,84 @@
+use std::io::{self, Write};
+
+use ipc::functions::{Log, LogArgs};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+
+use crate::WRITER;
+
+/// Lines sent in one ipc call at most
+const MAX_BATCH: usize = 64;
+
+/// Sender for queueing lines, and the receiver until `forward_task` takes it
+type Lines = (
+    mpsc::UnboundedSender<String>,
+    Mutex<Option<mpsc::UnboundedReceiver<String>>>,
+);
+
+static LINES: Lazy<Lines> = Lazy::new(|| {
+    let (send, recv) = mpsc::unbounded_channel();
+    (send, Mutex::new(Some(recv)))
+});
+
+/// Queues lines for `forward_task`, which may not be running yet
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Forward;
+
+impl Write for Forward {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Each event is written all at once
+        LINES
+            .0
+            .send(String::from_utf8_lossy(buf).into_owned())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "log forwarding stopped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for Forward {
+    type Writer = Forward;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        *self
+    }
+}
+
+/// Sends forwarded logs to the os, has to be spawned on a runtime if forwarding
+pub async fn forward_task() {
+    let Some(mut recv) = LINES.1.lock().take() else {
+        return;
+    };
+
+    while let Some(line) = recv.recv().await {
+        let mut lines = vec![line];
+        while lines.len() < MAX_BATCH {
+            match recv.try_recv() {
+                Ok(line) => lines.push(line),
+                Err(_) => break,
+            }
+        }
+
+        // Logging this would be forwarded too
+        if let Err(err) = ipc::client::call::<Log>(LogArgs { lines }).await {
+            eprintln!("Failed to forward logs: {err:?}");
+        }
+    }
+}
+
+/// Writes lines forwarded from another process to this process's log
+pub fn forwarded(lines: &[String]) {
+    let Some(writer) = WRITER.get() else {
+        return;
+    };
+
+    for line in lines {
+        if let Err(err) = writer.as_ref().write_all(line.as_bytes()) {
+            eprintln!("Failed to write forwarded log: {err:?}");
+        }
+    }
+}
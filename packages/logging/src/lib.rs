@@ -0,0 +1,197 @@
+//! Log setup shared by the os and emulator. Logs rotate once they get big, the filter can be
+//! changed while running, and the emulator can send its logs to the os instead of its own file
+
+mod forward;
+mod rotating;
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use layout::layout;
+use once_cell::sync::OnceCell;
+use tracing_subscriber::{
+    filter::filter_fn, fmt::writer::BoxMakeWriter, layer::SubscriberExt, reload,
+    util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
+
+pub use forward::{forward_task, forwarded};
+pub use rotating::Rotating;
+
+/// Env var with the filter to start with, in `EnvFilter` syntax
+pub const FILTER_ENV: &str = "OXIDE_LOG";
+/// Env var which sends logs to the os over ipc instead of to a file when set to 1
+pub const FORWARD_ENV: &str = "OXIDE_LOG_FORWARD";
+pub const DEFAULT_FILTER: &str = "info";
+/// Logs are rotated once they get this big
+pub const MAX_LOG_SIZE: u64 = 1024 * 1024;
+/// Rotated logs kept around, older ones are deleted
+pub const KEEP_LOGS: usize = 3;
+
+static FILTER: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+static WRITER: OnceCell<Arc<Rotating>> = OnceCell::new();
+
+/// Sets up logging for the process called `name`, which logs to `<logs>/<name>.log`
+pub fn init(name: &str) {
+    let filter = std::env::var(FILTER_ENV).unwrap_or_else(|_| DEFAULT_FILTER.into());
+    let forward = std::env::var(FORWARD_ENV).map_or(false, |forward| forward == "1");
+
+    let (filter_layer, handle) = reload::Layer::new(parse(&filter));
+    FILTER.set(handle).ok();
+
+    let writer = if forward {
+        BoxMakeWriter::new(forward::Forward)
+    } else {
+        let writer = Arc::new(Rotating::new(path(name), MAX_LOG_SIZE, KEEP_LOGS));
+        WRITER.set(writer.clone()).ok();
+        BoxMakeWriter::new(writer)
+    };
+
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .compact()
+        .with_ansi(false)
+        .with_writer(writer)
+        // Sending logs over ipc logs too, which would never end
+        .with_filter(filter_fn(move |meta| {
+            !forward || !meta.target().starts_with("hyper")
+        }));
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(fmt_layer)
+        .init();
+
+    if forward {
+        tracing::debug!("Forwarding logs to the os");
+    }
+}
+
+/// Changes the filter, bad filters are logged and ignored
+pub fn set_filter(filter: &str) {
+    let Some(handle) = FILTER.get() else {
+        return;
+    };
+
+    match EnvFilter::try_new(filter) {
+        Ok(filter) => {
+            if let Err(err) = handle.reload(filter) {
+                tracing::error!("Failed to change log filter: {err:?}");
+            }
+        }
+        Err(err) => tracing::error!("Bad log filter `{filter}`: {err:?}"),
+    }
+}
+
+/// Current log file for the process called `name`
+pub fn path(name: &str) -> PathBuf {
+    layout().logs().join(format!("{name}.log"))
+}
+
+/// Logs are read backwards in chunks this big until there are enough lines
+const TAIL_CHUNK: u64 = 16 * 1024;
+
+/// The last `count` lines of a log, for showing in the ui. Blocks, but only reads the end
+pub fn tail(name: &str, count: usize) -> io::Result<Vec<String>> {
+    tail_of(&path(name), count)
+}
+
+fn tail_of(path: &Path, count: usize) -> io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let mut start = file.metadata()?.len();
+    let mut buf = Vec::new();
+    let mut newlines = 0;
+
+    // One newline more than lines, the first line read may be cut off
+    while start > 0 && newlines <= count {
+        let size = TAIL_CHUNK.min(start);
+        start -= size;
+
+        let mut chunk = vec![0; size as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        newlines += chunk.iter().filter(|byte| **byte == b'\n').count();
+
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let log = String::from_utf8_lossy(&buf);
+    let mut lines = log
+        .lines()
+        .rev()
+        .take(count)
+        .map(String::from)
+        .collect::<Vec<_>>();
+    lines.reverse();
+    Ok(lines)
+}
+
+/// Deletes the timestamped `emu_log_<time>.log` files the emulator used to leave behind,
+/// one per game launched
+pub fn remove_old_emulator_logs() {
+    let entries = match std::fs::read_dir(layout().logs()) {
+        Ok(entries) => entries,
+        Err(err) => {
+            tracing::warn!("Failed to look for old emulator logs: {err:?}");
+            return;
+        }
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with("emu_log_") && name.ends_with(".log") {
+            match std::fs::remove_file(entry.path()) {
+                Ok(_) => removed += 1,
+                Err(err) => tracing::warn!("Failed to remove old log {name}: {err:?}"),
+            }
+        }
+    }
+
+    if removed > 0 {
+        tracing::info!("Removed {removed} old emulator logs");
+    }
+}
+
+fn parse(filter: &str) -> EnvFilter {
+    EnvFilter::try_new(filter).unwrap_or_else(|err| {
+        eprintln!("Bad log filter `{filter}`, using {DEFAULT_FILTER}: {err:?}");
+        EnvFilter::new(DEFAULT_FILTER)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail() {
+        let dir = std::env::temp_dir().join(format!("logging-{}-tail", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+
+        // Long enough to take a few chunks
+        let lines: Vec<String> = (0..5000)
+            .map(|i| format!("line {i} {}", "x".repeat(i % 20)))
+            .collect();
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        assert_eq!(tail_of(&path, 3).unwrap(), lines[4997..]);
+        assert_eq!(tail_of(&path, 2000).unwrap(), lines[3000..]);
+        assert_eq!(tail_of(&path, 10_000).unwrap(), lines);
+
+        std::fs::write(&path, "no newline at the end").unwrap();
+        assert_eq!(tail_of(&path, 5).unwrap(), vec!["no newline at the end"]);
+
+        std::fs::write(&path, "").unwrap();
+        assert!(tail_of(&path, 5).unwrap().is_empty());
+        assert_eq!(
+            tail_of(&dir.join("missing.log"), 5).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use parking_lot::Mutex;
+
+/// Log file which is moved to `<log>.1` once it gets too big, with older ones shifted up
+#[derive(Debug)]
+pub struct Rotating {
+    path: PathBuf,
+    max_size: u64,
+    /// Rotated logs kept besides the current one
+    keep: usize,
+    /// Opened on first write, along with its size
+    file: Mutex<Option<(File, u64)>>,
+}
+
+impl Rotating {
+    pub fn new(path: PathBuf, max_size: u64, keep: usize) -> Self {
+        Self {
+            path,
+            max_size,
+            keep,
+            file: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The `n`th rotated log, 1 being the newest
+    pub fn rotated(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{n}"));
+        path.into()
+    }
+
+    fn open(&self) -> io::Result<(File, u64)> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let size = file.metadata()?.len();
+        Ok((file, size))
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        if self.keep == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+
+        match std::fs::remove_file(self.rotated(self.keep)) {
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        for n in (1..self.keep).rev() {
+            match std::fs::rename(self.rotated(n), self.rotated(n + 1)) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        std::fs::rename(&self.path, self.rotated(1))
+    }
+}
+
+impl Write for &Rotating {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut file = self.file.lock();
+
+        if let Some((_, size)) = file.as_ref() {
+            if *size > 0 && size + buf.len() as u64 > self.max_size {
+                // Close it before moving it
+                *file = None;
+                self.rotate()?;
+            }
+        }
+
+        if file.is_none() {
+            *file = Some(self.open()?);
+        }
+        let (file, size) = file.as_mut().unwrap();
+
+        file.write_all(buf)?;
+        *size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.lock().as_mut() {
+            Some((file, _)) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Driver for the AXP223 PMIC on the Mini Plus, which handles charging and the fuel gauge
+
+use std::io;
+
+use i2cdev::core::I2CDevice;
+
+pub const AXP_BUS: &str = "/dev/i2c-1";
+pub const AXP_ADDRESS: u16 = 0x34;
+
+/// Bit 6 is set while charging
+const REG_CHARGE_STATUS: u8 = 0x01;
+/// High 8 bits of battery voltage, low 4 bits are in the next register
+const REG_BATTERY_VOLTAGE: u8 = 0x78;
+/// High 8 bits of charge current, low 4 bits are in the next register
+const REG_CHARGE_CURRENT: u8 = 0x7A;
+/// Bits 0-6 are the percentage, bit 7 is set once the gauge is calibrated
+const REG_FUEL_GAUGE: u8 = 0xB9;
+
+const CHARGING: u8 = 1 << 6;
+const GAUGE_PERCENTAGE: u8 = 0x7F;
+/// Microvolts per bit of battery voltage
+const VOLTAGE_STEP_UV: u32 = 1100;
+
+/// Works with any `I2CDevice`, so a fake one can stand in for the real PMIC
+#[derive(Debug)]
+pub struct Axp<D: I2CDevice> {
+    device: D,
+}
+
+impl<D: I2CDevice> Axp<D> {
+    pub fn new(device: D) -> Self {
+        Self { device }
+    }
+
+    /// Battery percentage from the PMIC's fuel gauge
+    pub fn gauge_percentage(&mut self) -> io::Result<u8> {
+        Ok((self.read(REG_FUEL_GAUGE)? & GAUGE_PERCENTAGE).min(100))
+    }
+
+    /// Battery voltage in millivolts
+    pub fn battery_voltage(&mut self) -> io::Result<u32> {
+        Ok(self.read_12_bit(REG_BATTERY_VOLTAGE)? * VOLTAGE_STEP_UV / 1000)
+    }
+
+    /// Current going into the battery in milliamps
+    pub fn charge_current(&mut self) -> io::Result<u32> {
+        self.read_12_bit(REG_CHARGE_CURRENT)
+    }
+
+    pub fn charging(&mut self) -> io::Result<bool> {
+        Ok(self.read(REG_CHARGE_STATUS)? & CHARGING != 0)
+    }
+
+    fn read(&mut self, register: u8) -> io::Result<u8> {
+        self.device
+            .smbus_read_byte_data(register)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+    }
+
+    /// Adc values are split with the high 8 bits in `register` and the low 4 in the next one
+    fn read_12_bit(&mut self, register: u8) -> io::Result<u32> {
+        let high = self.read(register)? as u32;
+        let low = self.read(register + 1)? as u32;
+        Ok((high << 4) | (low & 0x0F))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Register map standing in for the PMIC, keeps every register read and write in order
+    #[derive(Debug, Default)]
+    struct MockDevice {
+        registers: HashMap<u8, u8>,
+        reads: Vec<u8>,
+        writes: Vec<(u8, u8)>,
+    }
+
+    impl MockDevice {
+        fn with(registers: &[(u8, u8)]) -> Self {
+            Self {
+                registers: registers.iter().copied().collect(),
+                ..Default::default()
+            }
+        }
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "not used by the driver")
+    }
+
+    impl I2CDevice for MockDevice {
+        type Error = io::Error;
+
+        fn read(&mut self, _data: &mut [u8]) -> Result<(), Self::Error> {
+            Err(unsupported())
+        }
+
+        fn write(&mut self, _data: &[u8]) -> Result<(), Self::Error> {
+            Err(unsupported())
+        }
+
+        fn smbus_write_quick(&mut self, _bit: bool) -> Result<(), Self::Error> {
+            Err(unsupported())
+        }
+
+        fn smbus_read_byte_data(&mut self, register: u8) -> Result<u8, Self::Error> {
+            self.reads.push(register);
+            self.registers
+                .get(&register)
+                .copied()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unset register"))
+        }
+
+        fn smbus_write_byte_data(&mut self, register: u8, value: u8) -> Result<(), Self::Error> {
+            self.writes.push((register, value));
+            self.registers.insert(register, value);
+            Ok(())
+        }
+
+        fn smbus_read_block_data(&mut self, _register: u8) -> Result<Vec<u8>, Self::Error> {
+            Err(unsupported())
+        }
+
+        fn smbus_read_i2c_block_data(
+            &mut self,
+            _register: u8,
+            _len: u8,
+        ) -> Result<Vec<u8>, Self::Error> {
+            Err(unsupported())
+        }
+
+        fn smbus_write_block_data(
+            &mut self,
+            _register: u8,
+            _values: &[u8],
+        ) -> Result<(), Self::Error> {
+            Err(unsupported())
+        }
+
+        fn smbus_write_i2c_block_data(
+            &mut self,
+            _register: u8,
+            _values: &[u8],
+        ) -> Result<(), Self::Error> {
+            Err(unsupported())
+        }
+
+        fn smbus_process_block(
+            &mut self,
+            _register: u8,
+            _values: &[u8],
+        ) -> Result<Vec<u8>, Self::Error> {
+            Err(unsupported())
+        }
+    }
+
+    #[test]
+    fn gauge_percentage() {
+        for (register, percentage) in [
+            (0x00, 0),
+            (0x32, 50),
+            (0x64, 100),
+            // Calibrated bit is ignored
+            (0x80 | 0x4B, 75),
+            // Garbage above 100 is clamped
+            (0x7F, 100),
+        ] {
+            let mut axp = Axp::new(MockDevice::with(&[(REG_FUEL_GAUGE, register)]));
+            assert_eq!(
+                axp.gauge_percentage().unwrap(),
+                percentage,
+                "{register:#04x}"
+            );
+            assert_eq!(axp.device.reads, [REG_FUEL_GAUGE]);
+            assert!(axp.device.writes.is_empty());
+        }
+    }
+
+    #[test]
+    fn charging() {
+        for (register, charging) in [
+            (0x00, false),
+            (0x40, true),
+            // Other status bits don't matter
+            (0xBF, false),
+            (0x70, true),
+        ] {
+            let mut axp = Axp::new(MockDevice::with(&[(REG_CHARGE_STATUS, register)]));
+            assert_eq!(axp.charging().unwrap(), charging, "{register:#04x}");
+            assert_eq!(axp.device.reads, [REG_CHARGE_STATUS]);
+        }
+    }
+
+    #[test]
+    fn charge_current() {
+        for (high, low, milliamps) in [
+            (0x00, 0x00, 0),
+            // 1mA per bit, the low register only has 4 bits
+            (0x00, 0x01, 1),
+            (0x01, 0x00, 16),
+            (0x1F, 0x04, 500),
+            (0xFF, 0x0F, 4095),
+            // High bits of the low register are ignored
+            (0x1F, 0xF4, 500),
+        ] {
+            let mut axp = Axp::new(MockDevice::with(&[
+                (REG_CHARGE_CURRENT, high),
+                (REG_CHARGE_CURRENT + 1, low),
+            ]));
+            assert_eq!(
+                axp.charge_current().unwrap(),
+                milliamps,
+                "{high:#04x} {low:#04x}"
+            );
+            assert_eq!(
+                axp.device.reads,
+                [REG_CHARGE_CURRENT, REG_CHARGE_CURRENT + 1]
+            );
+        }
+    }
+
+    #[test]
+    fn battery_voltage() {
+        for (high, low, millivolts) in [
+            (0x00, 0x00, 0),
+            // 3818 * 1.1mV
+            (0xEE, 0x0A, 4199),
+            // 3273 * 1.1mV
+            (0xCC, 0x09, 3600),
+        ] {
+            let mut axp = Axp::new(MockDevice::with(&[
+                (REG_BATTERY_VOLTAGE, high),
+                (REG_BATTERY_VOLTAGE + 1, low),
+            ]));
+            assert_eq!(
+                axp.battery_voltage().unwrap(),
+                millivolts,
+                "{high:#04x} {low:#04x}"
+            );
+        }
+    }
+
+    #[test]
+    fn read_errors() {
+        let mut axp = Axp::new(MockDevice::default());
+        assert!(axp.gauge_percentage().is_err());
+        assert!(axp.charging().is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Everything the os needs from the hardware, so it can also run off device.
+//!
+//! The simulated backend is used when built with the `simulator` feature, or when
+//! `OXIDE_HAL` is set to `simulated`. `OXIDE_HAL=miyoo` forces the real one
+
+use std::{future::Future, io, time::Duration};
+
+use once_cell::sync::OnceCell;
+
+use crate::{charger::BatteryInfo, miyoo::Miyoo, model::Model, simulated::Simulated};
+
+static HAL: OnceCell<Hal> = OnceCell::new();
+
+/// Env var to pick the backend at runtime
+pub const HAL_ENV: &str = "OXIDE_HAL";
+
+pub trait Backend: Send + Sync {
+    /// Sets up anything that has to be before other calls, like gpio exports
+    fn init(&self) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn model(&self) -> impl Future<Output = io::Result<Model>> + Send;
+
+    fn battery_info(&self) -> impl Future<Output = io::Result<BatteryInfo>> + Send;
+
+    /// Between 0 and `screen::MAX_BRIGHTNESS`, also turns the backlight on
+    fn set_brightness(&self, brightness: u8) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Lowest backlight level, `set_brightness` brings it back
+    fn dim(&self) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn screen_off(&self) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn screen_on(&self) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Completes once the rumble is done
+    fn rumble(&self, duration: Duration) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Between 0 and `sound::MAX_VOLUME`
+    fn volume(&self) -> impl Future<Output = io::Result<u8>> + Send;
+
+    fn set_volume(&self, volume: u8) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Everything should be saved and synced before this
+    fn power_off(&self) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// Backend picked at startup
+#[derive(Debug)]
+pub enum Hal {
+    Miyoo(Miyoo),
+    Simulated(Simulated),
+}
+
+impl Hal {
+    pub fn is_simulated(&self) -> bool {
+        matches!(self, Self::Simulated(_))
+    }
+}
+
+/// The backend to use, picked on first call
+pub fn hal() -> &'static Hal {
+    HAL.get_or_init(|| {
+        let simulated = match std::env::var(HAL_ENV).as_deref() {
+            Ok("simulated") => true,
+            Ok("miyoo") => false,
+            Ok(other) => {
+                tracing::warn!("Unknown {HAL_ENV} `{other}`, using the default backend");
+                cfg!(feature = "simulator")
+            }
+            Err(_) => cfg!(feature = "simulator"),
+        };
+
+        if simulated {
+            tracing::info!("Using simulated hal");
+            Hal::Simulated(Simulated::new())
+        } else {
+            Hal::Miyoo(Miyoo)
+        }
+    })
+}
+
+/// Forwards a call to whichever backend is in use
+macro_rules! dispatch {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        async move {
+            match $self {
+                Hal::Miyoo(backend) => backend.$method($($arg),*).await,
+                Hal::Simulated(backend) => backend.$method($($arg),*).await,
+            }
+        }
+    };
+}
+
+impl Backend for Hal {
+    fn init(&self) -> impl Future<Output = io::Result<()>> + Send {
+        dispatch!(self.init())
+    }
+
+    fn model(&self) -> impl Future<Output = io::Result<Model>> + Send {
+        dispatch!(self.model())
+    }
+
+    fn battery_info(&self) -> impl Future<Output = io::Result<BatteryInfo>> + Send {
+        dispatch!(self.battery_info())
+    }
+
+    fn set_brightness(&self, brightness: u8) -> impl Future<Output = io::Result<()>> + Send {
+        dispatch!(self.set_brightness(brightness))
+    }
+
+    fn dim(&self) -> impl Future<Output = io::Result<()>> + Send {
+        dispatch!(self.dim())
+    }
+
+    fn screen_off(&self) -> impl Future<Output = io::Result<()>> + Send {
+        dispatch!(self.screen_off())
+    }
+
+    fn screen_on(&self) -> impl Future<Output = io::Result<()>> + Send {
+        dispatch!(self.screen_on())
+    }
+
+    fn rumble(&self, duration: Duration) -> impl Future<Output = io::Result<()>> + Send {
+        dispatch!(self.rumble(duration))
+    }
+
+    fn volume(&self) -> impl Future<Output = io::Result<u8>> + Send {
+        dispatch!(self.volume())
+    }
+
+    fn set_volume(&self, volume: u8) -> impl Future<Output = io::Result<()>> + Send {
+        dispatch!(self.set_volume(volume))
+    }
+
+    fn power_off(&self) -> impl Future<Output = io::Result<()>> + Send {
+        dispatch!(self.power_off())
+    }
+}
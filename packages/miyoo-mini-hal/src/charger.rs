@@ -1,22 +1,37 @@
 use std::io;
 
+use i2cdev::linux::LinuxI2CDevice;
 use once_cell::sync::OnceCell;
-use serde::Deserialize;
+use parking_lot::Mutex;
 
 use crate::{
+    asyncify,
+    axp::{Axp, AXP_ADDRESS, AXP_BUS},
     gpio::{Input, Pin},
     model::{model, Model},
+    sar,
 };
 
 static PIN: OnceCell<Pin<Input>> = OnceCell::new();
+static AXP: OnceCell<Mutex<Axp<LinuxI2CDevice>>> = OnceCell::new();
 
-#[derive(Debug, Deserialize)]
+/// The adc is noisy, so a few readings are averaged
+const ADC_SAMPLES: i32 = 5;
+/// Converts raw adc readings to millivolts, full charge reads about 578
+const ADC_TO_MV: f32 = 4200. / 578.;
+/// Millivolts to percentage, from OnionOS's adc curve. Linear between points
+const VOLTAGE_CURVE: &[(u32, u8)] = &[(3400, 0), (3488, 4), (3720, 20), (3837, 50), (4200, 100)];
+
+#[derive(Debug)]
 pub struct BatteryInfo {
     /// Battery percentage
     pub battery: u8,
+    /// Millivolts
     pub voltage: u32,
     /// 0 or 1
     pub charging: u8,
+    /// Milliamps, the Mini can't measure this so it's always 0 there
+    pub charge_current: u32,
 }
 
 /// This initializes charger detection using gpio on mini only
@@ -31,16 +46,62 @@ pub async fn init_charger_detection() -> io::Result<()> {
 pub async fn battery_info() -> io::Result<BatteryInfo> {
     let model = model().await?;
     match model {
-        Model::Mini => todo!(),
+        Model::Mini => {
+            let mut adc = 0;
+            for _ in 0..ADC_SAMPLES {
+                adc += sar::read_battery().await?;
+            }
+            let voltage = ((adc / ADC_SAMPLES) as f32 * ADC_TO_MV) as u32;
+
+            Ok(BatteryInfo {
+                battery: voltage_to_percentage(voltage),
+                voltage,
+                charging: pin().await?.value().await? as u8,
+                charge_current: 0,
+            })
+        }
         Model::MiniPlus => {
-            // TODO: find out why tokio::process:Command breaks everything
-            let mut output = std::process::Command::new("/customer/app/axp_test").output()?;
-            let info = serde_json::from_slice(&output.stdout).unwrap();
-            Ok(info)
+            asyncify(|| {
+                let mut axp = axp()?.lock();
+                Ok(BatteryInfo {
+                    battery: axp.gauge_percentage()?,
+                    voltage: axp.battery_voltage()?,
+                    charging: axp.charging()? as u8,
+                    charge_current: axp.charge_current()?,
+                })
+            })
+            .await
         }
     }
 }
 
+fn voltage_to_percentage(voltage: u32) -> u8 {
+    let (first, last) = (VOLTAGE_CURVE[0], VOLTAGE_CURVE[VOLTAGE_CURVE.len() - 1]);
+    if voltage <= first.0 {
+        return first.1;
+    }
+    if voltage >= last.0 {
+        return last.1;
+    }
+
+    VOLTAGE_CURVE
+        .windows(2)
+        .find(|points| voltage < points[1].0)
+        .map(|points| {
+            let ((low_mv, low), (high_mv, high)) = (points[0], points[1]);
+            let t = (voltage - low_mv) as f32 / (high_mv - low_mv) as f32;
+            low + ((high - low) as f32 * t) as u8
+        })
+        .unwrap_or(last.1)
+}
+
+fn axp() -> io::Result<&'static Mutex<Axp<LinuxI2CDevice>>> {
+    AXP.get_or_try_init(|| {
+        let device = LinuxI2CDevice::new(AXP_BUS, AXP_ADDRESS)?;
+        Ok(Mutex::new(Axp::new(device)))
+    })
+}
+
 async fn pin() -> io::Result<&'static Pin<Input>> {
     match PIN.get() {
         Some(pin) => Ok(pin),
@@ -51,3 +112,36 @@ async fn pin() -> io::Result<&'static Pin<Input>> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voltage_curve() {
+        // Clamped outside the curve
+        assert_eq!(voltage_to_percentage(0), 0);
+        assert_eq!(voltage_to_percentage(3000), 0);
+        assert_eq!(voltage_to_percentage(4500), 100);
+
+        // Exact on the curve's points
+        for &(voltage, percentage) in VOLTAGE_CURVE {
+            assert_eq!(voltage_to_percentage(voltage), percentage);
+        }
+
+        // Linear between them
+        assert_eq!(voltage_to_percentage(3444), 2);
+        assert_eq!(voltage_to_percentage(3604), 12);
+        assert_eq!(voltage_to_percentage(4020), 75);
+    }
+
+    #[test]
+    fn voltage_curve_monotonic() {
+        let mut prev = 0;
+        for voltage in 3300..4300 {
+            let percentage = voltage_to_percentage(voltage);
+            assert!(percentage >= prev, "{voltage}mV dropped to {percentage}%");
+            prev = percentage;
+        }
+    }
+}
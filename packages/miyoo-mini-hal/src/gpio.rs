@@ -88,6 +88,13 @@ impl Pin<Output> {
     }
 }
 
+impl Pin<Input> {
+    pub async fn value(&self) -> io::Result<bool> {
+        let value = tokio::fs::read(format!("/sys/class/gpio/gpio{}/value", self.num)).await?;
+        Ok(value.first() == Some(&b'1'))
+    }
+}
+
 impl<Dir: Direction> Drop for Pin<Dir> {
     fn drop(&mut self) {
         std::fs::OpenOptions::new()
@@ -1,11 +1,20 @@
+pub mod axp;
+mod backend;
 pub mod charger;
 pub mod gpio;
+mod miyoo;
 pub mod model;
 mod pwm;
 pub mod rumble;
+mod sar;
 pub mod screen;
+mod simulated;
 pub mod sound;
 
+pub use backend::{hal, Backend, Hal, HAL_ENV};
+pub use miyoo::Miyoo;
+pub use simulated::{Simulated, SIMULATED_BATTERY_ENV};
+
 /// Spawn a task on the blocking thread pool
 pub(crate) async fn asyncify<F, T>(f: F) -> std::io::Result<T>
 where
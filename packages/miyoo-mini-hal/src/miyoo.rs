@@ -0,0 +1,57 @@
+//! The real hardware, wraps the other modules of this crate
+
+use std::{future::Future, io, time::Duration};
+
+use crate::{backend::Backend, charger, model::Model, rumble, screen, sound};
+
+#[derive(Debug)]
+pub struct Miyoo;
+
+impl Backend for Miyoo {
+    fn init(&self) -> impl Future<Output = io::Result<()>> + Send {
+        charger::init_charger_detection()
+    }
+
+    fn model(&self) -> impl Future<Output = io::Result<Model>> + Send {
+        crate::model::model()
+    }
+
+    fn battery_info(&self) -> impl Future<Output = io::Result<charger::BatteryInfo>> + Send {
+        charger::battery_info()
+    }
+
+    fn set_brightness(&self, brightness: u8) -> impl Future<Output = io::Result<()>> + Send {
+        screen::change_brightness(brightness)
+    }
+
+    fn dim(&self) -> impl Future<Output = io::Result<()>> + Send {
+        screen::dim()
+    }
+
+    fn screen_off(&self) -> impl Future<Output = io::Result<()>> + Send {
+        screen::turn_off_screen()
+    }
+
+    fn screen_on(&self) -> impl Future<Output = io::Result<()>> + Send {
+        screen::turn_on_screen()
+    }
+
+    fn rumble(&self, duration: Duration) -> impl Future<Output = io::Result<()>> + Send {
+        rumble::rumble_for_sync(duration)
+    }
+
+    fn volume(&self) -> impl Future<Output = io::Result<u8>> + Send {
+        async { Ok(sound::get_volume().await? as u8) }
+    }
+
+    fn set_volume(&self, volume: u8) -> impl Future<Output = io::Result<()>> + Send {
+        sound::set_volume(volume as i32)
+    }
+
+    fn power_off(&self) -> impl Future<Output = io::Result<()>> + Send {
+        async {
+            tokio::process::Command::new("poweroff").output().await?;
+            Ok(())
+        }
+    }
+}
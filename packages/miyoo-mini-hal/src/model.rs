@@ -3,6 +3,8 @@ use std::io;
 use i2cdev::{core::*, linux::LinuxI2CDevice};
 use once_cell::sync::OnceCell;
 
+use crate::axp::{AXP_ADDRESS, AXP_BUS};
+
 /// Store the model of the device this is running on and reuse
 static DEVICE_MODEL: OnceCell<Model> = OnceCell::new();
 
@@ -45,7 +47,7 @@ pub async fn model() -> io::Result<Model> {
 /// Do some i2c stuff which will tell us if we are running on a plus
 async fn is_plus() -> io::Result<bool> {
     match tokio::task::spawn_blocking(|| {
-        let mut device = LinuxI2CDevice::new("/dev/i2c-1", 0x34)?;
+        let mut device = LinuxI2CDevice::new(AXP_BUS, AXP_ADDRESS)?;
         Ok(device.smbus_read_byte_data(0).is_ok())
     })
     .await
@@ -0,0 +1,59 @@
+//! SAR ADC on the original Mini, which has the battery on channel 0. Thanks to OnionOS for this too
+use std::{fs::File, io, os::fd::AsRawFd};
+
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+
+use crate::asyncify;
+
+static SAR: OnceCell<Mutex<File>> = OnceCell::new();
+
+const IOCTL_SAR_INIT: u32 = 0x6100;
+const IOCTL_SAR_SET_CHANNEL_READ_VALUE: u32 = 0x6101;
+const BATTERY_CHANNEL: i32 = 0;
+
+#[repr(C)]
+struct SarAdcConfigRead {
+    channel_value: i32,
+    adc_value: i32,
+}
+
+/// Raw 10 bit reading of the battery channel
+pub async fn read_battery() -> io::Result<i32> {
+    asyncify(|| {
+        let sar = sar()?.lock();
+        let mut config = SarAdcConfigRead {
+            channel_value: BATTERY_CHANNEL,
+            adc_value: 0,
+        };
+
+        // SAFETY: the driver only writes `adc_value` in the struct passed
+        if unsafe {
+            nix::libc::ioctl(
+                sar.as_raw_fd(),
+                IOCTL_SAR_SET_CHANNEL_READ_VALUE as _,
+                &mut config as *mut SarAdcConfigRead,
+            )
+        } < 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(config.adc_value)
+    })
+    .await
+}
+
+fn sar() -> io::Result<&'static Mutex<File>> {
+    SAR.get_or_try_init(|| {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/sar")?;
+        // SAFETY: takes no argument
+        if unsafe { nix::libc::ioctl(file.as_raw_fd(), IOCTL_SAR_INIT as _) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mutex::new(file))
+    })
+}
@@ -7,15 +7,34 @@ use crate::pwm;
 
 static SCREEN_PIN: OnceCell<tokio::sync::Mutex<Pin<'static>>> = OnceCell::new();
 
-/// Sets the pwm stuff to enable the backlight
-pub async fn change_brightness() -> io::Result<()> {
+const PERIOD_NS: u32 = 800;
+/// Brightness levels go from 0 to this
+pub const MAX_BRIGHTNESS: u8 = 10;
+/// Keeps the lowest brightness level readable
+const MIN_DUTY_NS: u32 = 5;
+
+/// Sets the backlight duty cycle for `brightness` between 0 and `MAX_BRIGHTNESS`, enabling it
+pub async fn change_brightness(brightness: u8) -> io::Result<()> {
     let mut pin = pin().await?;
-    pin.set_period_ns(800).await?;
-    pin.set_duty_cycle_ns(6).await?;
+    pin.set_period_ns(PERIOD_NS).await?;
+    pin.set_duty_cycle_ns(duty_cycle_ns(brightness)).await?;
     pin.enable().await?;
     Ok(())
 }
 
+/// Drops the backlight to its lowest level, `change_brightness` brings it back
+pub async fn dim() -> io::Result<()> {
+    let mut pin = pin().await?;
+    pin.set_duty_cycle_ns(MIN_DUTY_NS).await?;
+    Ok(())
+}
+
+/// Percent of the period, like OnionOS does
+fn duty_cycle_ns(brightness: u8) -> u32 {
+    let brightness = brightness.min(MAX_BRIGHTNESS) as u32;
+    (brightness * 10).max(MIN_DUTY_NS)
+}
+
 pub async fn turn_off_screen() -> io::Result<()> {
     let mut pin = pin().await?;
     pin.disable().await?;
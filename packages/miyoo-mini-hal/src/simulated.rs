@@ -0,0 +1,147 @@
+//! Stand in hardware for running on a desktop, it only keeps track of what was set
+
+use std::{
+    future::Future,
+    io,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{backend::Backend, charger::BatteryInfo, model::Model, screen, sound};
+
+/// Pins the simulated battery to a percentage, to try out low battery handling
+pub const SIMULATED_BATTERY_ENV: &str = "OXIDE_SIM_BATTERY";
+
+/// Simulated battery loses a percent this often
+const DRAIN_INTERVAL: Duration = Duration::from_secs(60);
+/// Draining stops here, above the low battery warnings and power off
+const MIN_BATTERY: u64 = 25;
+
+#[derive(Debug)]
+pub struct Simulated {
+    state: Mutex<State>,
+    started_at: Instant,
+    /// From `SIMULATED_BATTERY_ENV`, otherwise the battery drains
+    battery: Option<u8>,
+}
+
+#[derive(Debug)]
+struct State {
+    brightness: u8,
+    volume: u8,
+    screen_on: bool,
+}
+
+impl Simulated {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                brightness: screen::MAX_BRIGHTNESS,
+                volume: sound::MAX_VOLUME as u8 / 2,
+                screen_on: true,
+            }),
+            started_at: Instant::now(),
+            battery: std::env::var(SIMULATED_BATTERY_ENV)
+                .ok()
+                .and_then(|battery| match battery.parse::<u8>() {
+                    Ok(battery) => Some(battery.min(100)),
+                    Err(_) => {
+                        tracing::warn!("Invalid {SIMULATED_BATTERY_ENV} `{battery}`, draining");
+                        None
+                    }
+                }),
+        }
+    }
+}
+
+impl Default for Simulated {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backend for Simulated {
+    fn init(&self) -> impl Future<Output = io::Result<()>> + Send {
+        async { Ok(()) }
+    }
+
+    fn model(&self) -> impl Future<Output = io::Result<Model>> + Send {
+        async { Ok(Model::MiniPlus) }
+    }
+
+    fn battery_info(&self) -> impl Future<Output = io::Result<BatteryInfo>> + Send {
+        async {
+            let battery = self.battery.unwrap_or_else(|| {
+                let drained = self.started_at.elapsed().as_secs() / DRAIN_INTERVAL.as_secs();
+                100u64.saturating_sub(drained).max(MIN_BATTERY) as u8
+            });
+            Ok(BatteryInfo {
+                battery,
+                voltage: 3400 + battery as u32 * 8,
+                charging: 0,
+                charge_current: 0,
+            })
+        }
+    }
+
+    fn set_brightness(&self, brightness: u8) -> impl Future<Output = io::Result<()>> + Send {
+        async move {
+            tracing::debug!("Simulated brightness: {brightness}");
+            let mut state = self.state.lock();
+            state.brightness = brightness.min(screen::MAX_BRIGHTNESS);
+            state.screen_on = true;
+            Ok(())
+        }
+    }
+
+    fn dim(&self) -> impl Future<Output = io::Result<()>> + Send {
+        async {
+            tracing::debug!("Simulated dim");
+            Ok(())
+        }
+    }
+
+    fn screen_off(&self) -> impl Future<Output = io::Result<()>> + Send {
+        async {
+            tracing::debug!("Simulated screen off");
+            self.state.lock().screen_on = false;
+            Ok(())
+        }
+    }
+
+    fn screen_on(&self) -> impl Future<Output = io::Result<()>> + Send {
+        async {
+            tracing::debug!("Simulated screen on");
+            self.state.lock().screen_on = true;
+            Ok(())
+        }
+    }
+
+    fn rumble(&self, duration: Duration) -> impl Future<Output = io::Result<()>> + Send {
+        async move {
+            tracing::debug!("Simulated rumble for {duration:?}");
+            tokio::time::sleep(duration).await;
+            Ok(())
+        }
+    }
+
+    fn volume(&self) -> impl Future<Output = io::Result<u8>> + Send {
+        async { Ok(self.state.lock().volume) }
+    }
+
+    fn set_volume(&self, volume: u8) -> impl Future<Output = io::Result<()>> + Send {
+        async move {
+            tracing::debug!("Simulated volume: {volume}");
+            self.state.lock().volume = volume.min(sound::MAX_VOLUME as u8);
+            Ok(())
+        }
+    }
+
+    fn power_off(&self) -> impl Future<Output = io::Result<()>> + Send {
+        async {
+            tracing::info!("Simulated power off, exiting");
+            std::process::exit(0)
+        }
+    }
+}
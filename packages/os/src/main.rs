@@ -1,24 +1,22 @@
+use miyoo_mini_hal::hal;
+
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter("debug")
-        .compact()
-        .with_ansi(false)
-        .with_writer(
-            std::fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(true)
-                .open("/mnt/SDCARD/miyoo/app/os.log")
-                .unwrap(),
-        )
-        .init();
+    let layout = layout::init_from_args();
+    // Settings aren't loaded yet, they set the filter once they are
+    logging::init("os");
+    logging::remove_old_emulator_logs();
+    // Picking the hal logs which one was found, so it has to come after logging
+    let simulated = hal().is_simulated();
 
     std::env::set_var("RUST_BACKTRACE", "full");
-    std::env::set_var("WINIT_UNIX_BACKEND", "wayland");
-    std::env::set_var("HOME", "/mnt/SDCARD/miyoo/app");
+    // On a desktop, leave the window system and home alone
+    if !simulated {
+        std::env::set_var("WINIT_UNIX_BACKEND", "wayland");
+        std::env::set_var("HOME", layout.app());
+    }
 
     ui::start();
 }
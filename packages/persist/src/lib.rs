@@ -0,0 +1,236 @@
+//! Crash safe file writes. Files are written to a temp file, synced and then renamed
+//! over the original, which is kept as `<file>.bak` in case the new one turns out bad.
+
+use std::{
+    ffi::OsString,
+    fmt::Debug,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Where the previous version of `path` is kept
+pub fn backup_path(path: impl AsRef<Path>) -> PathBuf {
+    with_suffix(path.as_ref(), ".bak")
+}
+
+/// Unique per write, so concurrent writes to the same file don't clobber each other's temp file
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    with_suffix(path, &format!(".{}.{count}.tmp", std::process::id()))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Atomically replaces `path` with `contents`, moving the old file to the backup
+pub fn write_sync(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+    let temp = temp_path(path);
+
+    let mut file = File::create(&temp)?;
+    file.write_all(contents.as_ref())?;
+    file.sync_all()?;
+    drop(file);
+
+    // If we die between these renames, reading falls back to the backup
+    match std::fs::rename(path, backup_path(path)) {
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    std::fs::rename(&temp, path)?;
+
+    // Make sure the renames themselves hit the sd card
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+/// Reads `path` and parses it, falling back to the backup if the file is missing or `parse` fails.
+///
+/// Returns `NotFound` if neither exist and `InvalidData` if neither could be parsed
+pub fn read_sync_with<T, E: Debug>(
+    path: impl AsRef<Path>,
+    parse: impl Fn(Vec<u8>) -> Result<T, E>,
+) -> io::Result<T> {
+    let path = path.as_ref();
+
+    let main_missing = match std::fs::read(path) {
+        Ok(bytes) => match parse(bytes) {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                tracing::error!("{} is corrupt, trying backup: {err:?}", path.display());
+                false
+            }
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => true,
+        Err(err) => return Err(err),
+    };
+
+    match std::fs::read(backup_path(path)) {
+        Ok(bytes) => {
+            let value = parse(bytes).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} and its backup are corrupt: {err:?}", path.display()),
+                )
+            })?;
+            tracing::warn!("Recovered {} from backup", path.display());
+            Ok(value)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound && !main_missing => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is corrupt and has no backup", path.display()),
+        )),
+        Err(err) => Err(err),
+    }
+}
+
+/// `write_sync` on the blocking thread pool
+pub async fn write(path: impl Into<PathBuf>, contents: Vec<u8>) -> io::Result<()> {
+    let path = path.into();
+    asyncify(move || write_sync(path, contents)).await
+}
+
+/// `read_sync_with` on the blocking thread pool
+pub async fn read_with<T, E>(
+    path: impl Into<PathBuf>,
+    parse: impl Fn(Vec<u8>) -> Result<T, E> + Send + 'static,
+) -> io::Result<T>
+where
+    T: Send + 'static,
+    E: Debug,
+{
+    let path = path.into();
+    asyncify(move || read_sync_with(path, parse)).await
+}
+
+/// Spawn a task on the blocking thread pool
+async fn asyncify<F, T>(f: F) -> std::io::Result<T>
+where
+    F: FnOnce() -> std::io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(res) => res,
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "background task failed",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory per test so they can run in parallel
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("persist-{}-{name}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read(path: &Path) -> io::Result<String> {
+        read_sync_with(path, String::from_utf8)
+    }
+
+    /// Not valid utf8, so `read` fails to parse it
+    const CORRUPT: &[u8] = &[0xff, 0xfe];
+
+    #[test]
+    fn write_keeps_backup() {
+        let path = test_dir("write").join("file.json");
+
+        write_sync(&path, "first").unwrap();
+        assert_eq!(read(&path).unwrap(), "first");
+        assert!(!backup_path(&path).exists());
+
+        write_sync(&path, "second").unwrap();
+        assert_eq!(read(&path).unwrap(), "second");
+        assert_eq!(
+            std::fs::read_to_string(backup_path(&path)).unwrap(),
+            "first"
+        );
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            2
+        );
+    }
+
+    #[test]
+    fn concurrent_writes() {
+        let path = test_dir("concurrent").join("file.json");
+
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        write_sync(&path, format!("writer {i}")).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert!(read(&path).unwrap().starts_with("writer "));
+        // Only the file and its backup are left, no stray temp files
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            2
+        );
+    }
+
+    #[test]
+    fn corrupt_falls_back() {
+        let path = test_dir("corrupt").join("file.json");
+
+        write_sync(&path, "good").unwrap();
+        write_sync(&path, CORRUPT).unwrap();
+        assert_eq!(read(&path).unwrap(), "good");
+    }
+
+    #[test]
+    fn missing_falls_back() {
+        let path = test_dir("missing").join("file.json");
+
+        // Died between moving the file to the backup and moving the new one in
+        std::fs::write(backup_path(&path), "good").unwrap();
+        assert_eq!(read(&path).unwrap(), "good");
+    }
+
+    #[test]
+    fn read_errors() {
+        let dir = test_dir("errors");
+
+        let missing = dir.join("missing.json");
+        assert_eq!(read(&missing).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        let no_backup = dir.join("no_backup.json");
+        std::fs::write(&no_backup, CORRUPT).unwrap();
+        assert_eq!(
+            read(&no_backup).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let both_corrupt = dir.join("both_corrupt.json");
+        std::fs::write(&both_corrupt, CORRUPT).unwrap();
+        std::fs::write(backup_path(&both_corrupt), CORRUPT).unwrap();
+        assert_eq!(
+            read(&both_corrupt).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
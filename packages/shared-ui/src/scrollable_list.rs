@@ -6,6 +6,9 @@ use iced::{
     Command, Element, Length,
 };
 
+/// Items skipped by a page jump
+const PAGE_SIZE: usize = 8;
+
 #[derive(Debug)]
 pub struct ScrollableList<A: iced::Application> {
     items: Vec<ListItem<A>>,
@@ -14,6 +17,8 @@ pub struct ScrollableList<A: iced::Application> {
 }
 
 pub struct ListItem<A: iced::Application> {
+    /// Letter used for alphabet jumps
+    letter: Option<char>,
     children: Arc<dyn for<'a> Fn(&'a A) -> Element<'static, A::Message> + Send + Sync>,
     action: Arc<dyn Fn(&mut A, A::Message) -> Command<A::Message> + Send + Sync>,
 }
@@ -21,6 +26,12 @@ pub struct ListItem<A: iced::Application> {
 pub enum Message {
     Up,
     Down,
+    PageUp,
+    PageDown,
+    /// Jump to the first item of the previous letter
+    PrevLetter,
+    /// Jump to the first item of the next letter
+    NextLetter,
     Other,
 }
 
@@ -30,10 +41,17 @@ impl<A: iced::Application> ListItem<A> {
         action: impl Fn(&mut A, A::Message) -> Command<A::Message> + Send + Sync + 'static,
     ) -> Self {
         Self {
+            letter: None,
             children: Arc::new(children),
             action: Arc::new(action),
         }
     }
+
+    /// Groups this item under `letter` for `PrevLetter`/`NextLetter`
+    pub fn with_letter(mut self, letter: char) -> Self {
+        self.letter = Some(letter);
+        self
+    }
 }
 
 impl<A: iced::Application> std::fmt::Debug for ListItem<A> {
@@ -54,6 +72,16 @@ where
         }
     }
 
+    /// Index of the currently selected item
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects the item at `idx`, clamped to the last item
+    pub fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.items.len().saturating_sub(1));
+    }
+
     /// Handle message
     pub fn update(
         &mut self,
@@ -72,7 +100,7 @@ where
                 } else {
                     self.selected = self.items.len() - 1;
                 }
-                Command::none()
+                self.snap_to_selected()
             }
             Message::Down => {
                 if self.selected < self.items.len() - 1 {
@@ -80,7 +108,40 @@ where
                 } else {
                     self.selected = 0;
                 }
-                Command::none()
+                self.snap_to_selected()
+            }
+            Message::PageUp => {
+                self.selected = self.selected.saturating_sub(PAGE_SIZE);
+                self.snap_to_selected()
+            }
+            Message::PageDown => {
+                self.selected = (self.selected + PAGE_SIZE).min(self.items.len() - 1);
+                self.snap_to_selected()
+            }
+            Message::PrevLetter => {
+                let letter = self.items[self.selected].letter;
+                // Skip back over the current letter, then to the start of the one before it
+                let mut idx = self.selected;
+                while idx > 0 && self.items[idx - 1].letter == letter {
+                    idx -= 1;
+                }
+                if idx > 0 {
+                    let prev = self.items[idx - 1].letter;
+                    idx -= 1;
+                    while idx > 0 && self.items[idx - 1].letter == prev {
+                        idx -= 1;
+                    }
+                }
+                self.selected = idx;
+                self.snap_to_selected()
+            }
+            Message::NextLetter => {
+                let letter = self.items[self.selected].letter;
+                self.selected = self.items[self.selected..]
+                    .iter()
+                    .position(|item| item.letter != letter)
+                    .map_or(self.items.len() - 1, |offset| self.selected + offset);
+                self.snap_to_selected()
             }
             Message::Other => {
                 let ListItem { action, .. } = &self.items[self.selected];
@@ -89,6 +150,17 @@ where
         }
     }
 
+    /// Scrolls so the selected item is in view, assumes items are all about the same height
+    fn snap_to_selected(&self) -> Command<A::Message> {
+        let y = if self.items.len() > 1 {
+            self.selected as f32 / (self.items.len() - 1) as f32
+        } else {
+            0.
+        };
+
+        scrollable::snap_to(self.id.clone(), scrollable::RelativeOffset { x: 0., y })
+    }
+
     pub fn view(&self, app: &A) -> Element<'static, A::Message> {
         let Self {
             id,
@@ -129,6 +201,7 @@ impl<A: iced::Application> Clone for ScrollableList<A> {
 impl<A: iced::Application> Clone for ListItem<A> {
     fn clone(&self) -> Self {
         Self {
+            letter: self.letter,
             children: self.children.clone(),
             action: self.action.clone(),
         }
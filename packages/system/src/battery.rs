@@ -1,23 +1,171 @@
-use std::time::Duration;
+use std::{
+    io,
+    time::{Duration, SystemTime},
+};
 
-use miyoo_mini_hal::charger::battery_info;
-use tokio::sync::mpsc;
+use ipc::functions::{Overlay, OverlayKind, ShowOverlay, ShowOverlayArgs};
+use layout::layout;
+use miyoo_mini_hal::{charger::BatteryInfo, hal, Backend};
+use tokio::{io::AsyncWriteExt, sync::mpsc};
 
-use crate::{sleep::sleeping, SystemMessage};
+use crate::{
+    emulator, input_task, settings,
+    sleep::{deep_sleep, sleeping},
+    SystemMessage,
+};
+
+/// Percentage, voltage and charging state over time, one line per change
+const BATTERY_LOG_FILE: &str = "battery_log.csv";
+/// Log is moved to `<log>.old` once it gets this big
+const MAX_BATTERY_LOG_SIZE: u64 = 512 * 1024;
+/// Consecutive critical readings before powering off, the gauge sometimes glitches low
+const CRITICAL_READINGS: u32 = 3;
+/// Bigger drops between two readings are treated as glitches
+const MAX_BATTERY_DROP: u8 = 10;
 
 /// Task which gets battery info and sends to ui every 10 seconds
 pub async fn battery(battery_sender: mpsc::Sender<SystemMessage>) {
+    let mut last: Option<BatteryInfo> = None;
+    // Glitches are compared against this rather than `last`, so the reading after one isn't
+    // taken as plausible just for being close to the glitch
+    let mut last_plausible: Option<u8> = None;
+    // Lowest warning threshold shown since last charging
+    let mut warned_at: Option<u8> = None;
+    let mut critical_readings = 0;
+
     loop {
-        let info = battery_info().await.unwrap();
+        let info = hal().battery_info().await.unwrap();
+        let charging = info.charging != 0;
+        let settings = settings::current();
+
+        // Voltage jitters, only log what matters for rates
+        if last.as_ref().map_or(true, |last| {
+            last.battery != info.battery || last.charging != info.charging
+        }) {
+            if let Err(err) = log(&info).await {
+                tracing::error!("Failed to log battery: {err:?}");
+            }
+        }
+
+        // Glitched readings neither count towards nor reset the critical readings
+        let plausible = info.battery != 0
+            && last_plausible.map_or(true, |last_plausible| {
+                last_plausible.saturating_sub(info.battery) <= MAX_BATTERY_DROP
+            });
+        if plausible {
+            last_plausible = Some(info.battery);
+        }
+        if charging || info.battery > settings.critical_battery {
+            critical_readings = 0;
+        } else if plausible {
+            critical_readings += 1;
+        } else {
+            tracing::warn!("Ignoring implausible battery reading of {}%", info.battery);
+        }
+
+        if critical_readings >= CRITICAL_READINGS {
+            tracing::warn!("Battery critical at {}%, powering off", info.battery);
+            if sleeping() {
+                deep_sleep(&battery_sender).await;
+            } else {
+                input_task::power_off(&battery_sender).await;
+            }
+        }
+
+        if charging {
+            warned_at = None;
+        } else if let Some(threshold) = settings
+            .battery_warnings
+            .iter()
+            .copied()
+            .filter(|threshold| info.battery <= *threshold)
+            .min()
+            .filter(|threshold| warned_at.map_or(true, |warned_at| *threshold < warned_at))
+        {
+            warned_at = Some(threshold);
+            if !sleeping() {
+                warn(info.battery, &battery_sender).await;
+            }
+        }
 
         if !sleeping() {
             // Only update the ui if not sleeping
+            if last
+                .as_ref()
+                .map_or(true, |last| (last.charging != 0) != charging)
+            {
+                battery_sender
+                    .send(SystemMessage::Charging(charging))
+                    .await
+                    .unwrap();
+            }
+
             battery_sender
                 .send(SystemMessage::BatteryPercentage(info.battery))
                 .await
                 .unwrap();
         }
 
+        last = Some(info);
         tokio::time::sleep(Duration::from_secs(10)).await;
     }
 }
+
+/// Tells the ui, and the emulator if playing, that the battery is getting low
+async fn warn(percentage: u8, sender: &mpsc::Sender<SystemMessage>) {
+    tracing::debug!("Low battery warning at {percentage}%");
+    sender
+        .send(SystemMessage::LowBattery(percentage))
+        .await
+        .unwrap();
+
+    if emulator::playing() {
+        let overlay = Overlay {
+            kind: OverlayKind::Battery,
+            level: percentage,
+            max: 100,
+        };
+        if let Err(err) = ipc::client::call::<ShowOverlay>(ShowOverlayArgs { overlay }).await {
+            tracing::error!("Failed to show battery overlay: {err:?}");
+        }
+    }
+}
+
+/// Appends to the battery log, used to estimate drain and charge rates
+async fn log(info: &BatteryInfo) -> io::Result<()> {
+    match tokio::fs::metadata(layout().data(BATTERY_LOG_FILE)).await {
+        Ok(metadata) if metadata.len() >= MAX_BATTERY_LOG_SIZE => {
+            tokio::fs::rename(
+                layout().data(BATTERY_LOG_FILE),
+                layout().data(&format!("{BATTERY_LOG_FILE}.old")),
+            )
+            .await?;
+        }
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(layout().data(BATTERY_LOG_FILE))
+        .await?;
+
+    if file.metadata().await?.len() == 0 {
+        file.write_all(b"time,percentage,voltage,charging\n")
+            .await?;
+    }
+
+    let time = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs());
+    file.write_all(
+        format!(
+            "{time},{},{},{}\n",
+            info.battery, info.voltage, info.charging
+        )
+        .as_bytes(),
+    )
+    .await
+}
@@ -1,14 +1,40 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
-use ipc::functions::{SaveState, SaveStateArgs};
+use ipc::{
+    functions::{
+        FlushSram, FlushSramArgs, Function, Hello, HelloArgs, SaveState, SaveStateArgs,
+        ShowOverlay, Start, Stop, PROTOCOL_VERSION,
+    },
+    ErrorCode,
+};
+use layout::layout;
 use once_cell::sync::OnceCell;
 use tokio::sync::mpsc;
 
-use crate::{games::Game, SystemMessage};
+use crate::{games::Game, history, play_time, settings, SystemMessage};
 
-static SENDER: OnceCell<mpsc::Sender<Option<tokio::process::Child>>> = OnceCell::new();
+/// Emulator proc along with its play time session id
+static SENDER: OnceCell<mpsc::Sender<Option<(tokio::process::Child, u64)>>> = OnceCell::new();
 static PLAYING: AtomicBool = AtomicBool::new(false);
 
+/// Game that was running, only there if the device went off without exiting it
+const RESUME_FILE: &str = "resume.json";
+
+/// Functions the os calls on the emulator, checked for after it starts
+const REQUIRED_FUNCTIONS: &[fn() -> &'static str] = &[
+    SaveState::path,
+    FlushSram::path,
+    Stop::path,
+    Start::path,
+    ShowOverlay::path,
+];
+/// `Hello` is retried for this long while the emulator starts up
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub fn playing() -> bool {
     PLAYING.load(Ordering::Relaxed)
 }
@@ -17,77 +43,249 @@ pub fn playing() -> bool {
 pub fn play(game: &Game) {
     tracing::debug!("Playing game: {}", game.as_path().display());
 
+    let settings = settings::current();
     let proc = tokio::process::Command::new("emulator")
         .args([
-            format!("/mnt/SDCARD/Cores/{}_libretro.so", game.core()),
-            format!("{}", game.as_path().display()),
+            layout().core(game.core()).into_os_string(),
+            game.as_path().into(),
             "--load-auto".into(),
+            layout::ROOT_FLAG.into(),
+            layout().root().into(),
         ])
+        .env(logging::FILTER_ENV, &settings.log_filter)
+        .env(
+            logging::FORWARD_ENV,
+            if settings.forward_emulator_logs {
+                "1"
+            } else {
+                "0"
+            },
+        )
         .spawn()
         .unwrap();
 
-    SENDER.get().unwrap().try_send(Some(proc)).unwrap();
+    let resume = serde_json::to_vec_pretty(game).unwrap();
+    tokio::spawn(async move {
+        if let Err(err) = persist::write(layout().data(RESUME_FILE), resume).await {
+            tracing::error!("Failed to record game to resume: {err:?}");
+        }
+    });
+
+    history::record_start(game);
+    let session = play_time::start(game);
+
+    SENDER
+        .get()
+        .unwrap()
+        .try_send(Some((proc, session)))
+        .unwrap();
 
     PLAYING.store(true, Ordering::Relaxed);
 }
 
-/// Stops emulator proc along with creating a save state
+/// Stops emulator proc along with creating a save state, going back to the menu
 pub async fn stop_playing() -> Result<(), String> {
+    stop(true).await
+}
+
+/// Like `stop_playing`, but the game is resumed on next boot
+pub(crate) async fn stop_for_power_off() -> Result<(), String> {
+    stop(false).await
+}
+
+async fn stop(clean_exit: bool) -> Result<(), String> {
     if !PLAYING.load(Ordering::Relaxed) {
         tracing::warn!("Tried to kill emulator while not playing.");
         return Ok(());
     }
 
     // Firstly, tell emulator to save into the auto slot
-    let _ = match ipc::client::call::<SaveState>(SaveStateArgs { slot: None }).await {
-        Ok(res) => {
-            if res.status().is_server_error() {
-                // Failed again
-                tracing::error!("Emulator error while saving.");
-                Err("Error while saving state".to_string())
-            } else {
-                Ok(())
-            }
-        }
-        Err(err) => {
-            tracing::error!("Hyper error while saving: {err:?}");
-            Err("Error while saving state".to_string())
-        }
-    }?;
+    if let Err(err) = ipc::client::call::<SaveState>(SaveStateArgs { slot: None }).await {
+        tracing::error!("Error while saving: {err:?}");
+        return Err(format!("Error while saving state: {}", err.message));
+    }
+
+    // The proc is killed, so in game saves have to be written out first.
+    // Still kill it if this fails, a hung emulator shouldn't trap the player in game
+    let flushed = ipc::client::call::<FlushSram>(FlushSramArgs {})
+        .await
+        .map_err(|err| {
+            tracing::error!("Error while flushing save ram: {err:?}");
+            format!("Error while flushing save ram: {}", err.message)
+        });
 
     // Now, tell task to kill the emulator process
     SENDER.get().unwrap().try_send(None).unwrap();
 
+    if let Some(session) = play_time::current() {
+        end_session(session);
+    }
+
     // Set flag
     PLAYING.store(false, Ordering::Relaxed);
 
+    if clean_exit {
+        clear_resume().await;
+    }
+
+    flushed
+}
+
+/// Kills the emulator without saving, for when it can't be talked to
+async fn kill() {
+    SENDER.get().unwrap().try_send(None).unwrap();
+
+    if let Some(session) = play_time::current() {
+        end_session(session);
+    }
+
+    PLAYING.store(false, Ordering::Relaxed);
+    // Don't boot right back into it
+    clear_resume().await;
+}
+
+/// Closes the play time session
+fn end_session(session: u64) {
+    play_time::stop(session);
+}
+
+/// Makes sure the emulator speaks the same ipc as the os, since they're deployed separately
+async fn check_emulator() -> Result<(), String> {
+    let start = std::time::Instant::now();
+    let capabilities = loop {
+        match ipc::client::call::<Hello>(HelloArgs {
+            version: PROTOCOL_VERSION,
+        })
+        .await
+        {
+            Ok(capabilities) => break capabilities,
+            // Still loading the core
+            Err(err) if err.code == ErrorCode::Unavailable && start.elapsed() < HELLO_TIMEOUT => {}
+            Err(err) if err.code == ErrorCode::NotFound => {
+                return Err("Emulator is older than the os, update both together".into())
+            }
+            Err(err) => return Err(format!("Couldn't reach the emulator: {}", err.message)),
+        }
+    };
+
+    tracing::debug!("Emulator capabilities: {capabilities:?}");
+
+    if capabilities.version != PROTOCOL_VERSION {
+        return Err(format!(
+            "Emulator {} speaks ipc version {}, the os needs {PROTOCOL_VERSION}. Update both together",
+            capabilities.build, capabilities.version
+        ));
+    }
+
+    let missing = REQUIRED_FUNCTIONS
+        .iter()
+        .map(|path| path())
+        .filter(|path| {
+            !capabilities
+                .functions
+                .iter()
+                .any(|function| function == path)
+        })
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Emulator {} is missing {}. Update both together",
+            capabilities.build,
+            missing.join(", ")
+        ));
+    }
+
     Ok(())
 }
 
-pub(crate) async fn task(event_sender: mpsc::Sender<SystemMessage>) {
-    let (proc_sender, mut proc_recv) = mpsc::channel(1);
-    let mut proc_id: Option<u32> = None;
+/// The game to relaunch, if the device was powered off while playing
+pub(crate) async fn resume_game() -> Option<Game> {
+    let game = match persist::read_with(layout().data(RESUME_FILE), |bytes| {
+        serde_json::from_slice::<Game>(&bytes)
+    })
+    .await
+    {
+        Ok(game) => game,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            tracing::error!("Failed to read game to resume: {err:?}");
+            return None;
+        }
+    };
 
+    // Could have been deleted or the sd card swapped
+    if !game.exists().await {
+        tracing::warn!("Game to resume is gone: {}", game.as_path().display());
+        clear_resume().await;
+        return None;
+    }
+
+    Some(game)
+}
+
+async fn clear_resume() {
+    for path in [
+        layout().data(RESUME_FILE),
+        persist::backup_path(layout().data(RESUME_FILE)),
+    ] {
+        match tokio::fs::remove_file(&path).await {
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => tracing::error!("Failed to remove {}: {err:?}", path.display()),
+        }
+    }
+}
+
+/// Has to be called before `play`
+pub(crate) fn init() -> mpsc::Receiver<Option<(tokio::process::Child, u64)>> {
+    let (proc_sender, proc_recv) = mpsc::channel(1);
     SENDER.set(proc_sender).unwrap();
+    proc_recv
+}
+
+pub(crate) async fn task(
+    event_sender: mpsc::Sender<SystemMessage>,
+    mut proc_recv: mpsc::Receiver<Option<(tokio::process::Child, u64)>>,
+) {
+    let mut proc_id: Option<u32> = None;
 
     while let Some(proc) = proc_recv.recv().await {
         match proc {
-            Some(mut proc) => {
+            Some((mut proc, session)) => {
                 tracing::debug!("Starting an emulator proc");
                 let event_sender = event_sender.clone();
                 proc_id = proc.id();
 
+                let check_sender = event_sender.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = check_emulator().await {
+                        tracing::error!("Emulator check failed: {err}");
+                        // A crash is already reported
+                        if !playing() {
+                            return;
+                        }
+                        // Saving would go through the same ipc that just failed
+                        kill().await;
+                        check_sender.send(SystemMessage::Error(err)).await.unwrap();
+                        check_sender.send(SystemMessage::MainMenu).await.unwrap();
+                    }
+                });
+
                 // Monitor the status of the emulator process
                 tokio::spawn(async move {
                     tracing::debug!("Started emu watch task.");
                     let status = proc.wait().await;
                     tracing::debug!("Emulator proc ended.");
                     PLAYING.store(false, Ordering::Relaxed);
+                    // Already closed by `stop_playing` unless the emulator crashed
+                    end_session(session);
                     let status = status.unwrap();
                     if let Some(code) = status.code() {
                         // It was only a crash if there is an exit code
                         // Otherwise it was killed by a signal which is intended
                         tracing::debug!("Emulator proc crashed.");
+                        // Don't boot right back into a crash
+                        clear_resume().await;
                         event_sender
                             .send(SystemMessage::Error(format!(
                                 "Emulator crashed with status: {}",
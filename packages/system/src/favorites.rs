@@ -0,0 +1,116 @@
+use std::{io, path::PathBuf};
+
+use layout::layout;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::games::Game;
+
+pub(crate) static FAVORITES_SENDER: OnceCell<mpsc::Sender<Favorites>> = OnceCell::new();
+
+const FAVORITES_FILE: &str = "favorites.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Favorites {
+    games: Vec<Game>,
+    /// Favorites whose rom couldn't be found on startup
+    #[serde(skip)]
+    missing: Vec<PathBuf>,
+}
+
+impl Favorites {
+    pub async fn init() -> io::Result<(Self, mpsc::Receiver<Self>)> {
+        let mut favorites = match persist::read_with(layout().data(FAVORITES_FILE), |bytes| {
+            serde_json::from_slice::<Favorites>(&bytes)
+        })
+        .await
+        {
+            Ok(favorites) => {
+                tracing::debug!("Found {} favorites", favorites.games.len());
+                favorites
+            }
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                tracing::error!("Found invalid favorites file. Starting empty. {err:?}");
+                Favorites::default()
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                tracing::debug!("No favorites file.");
+                Favorites::default()
+            }
+            Err(err) => return Err(err),
+        };
+
+        // Keep missing favorites around in case the sd card is just being reorganized
+        for game in &favorites.games {
+            if !game.exists().await {
+                tracing::warn!("Favorite is missing: {}", game.as_path().display());
+                favorites.missing.push(game.as_path().to_path_buf());
+            }
+        }
+
+        let (send, recv) = mpsc::channel(16);
+        FAVORITES_SENDER.set(send).ok();
+
+        Ok((favorites, recv))
+    }
+
+    #[inline]
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
+
+    pub fn contains(&self, game: &Game) -> bool {
+        self.games
+            .iter()
+            .any(|favorite| favorite.as_path() == game.as_path())
+    }
+
+    /// Whether the rom for this favorite was missing on startup
+    pub fn is_missing(&self, game: &Game) -> bool {
+        self.missing.iter().any(|path| path == game.as_path())
+    }
+
+    /// Adds `game` if it isn't a favorite, otherwise removes it
+    pub fn toggle(&mut self, game: &Game) {
+        if self.contains(game) {
+            self.remove(game);
+        } else {
+            self.games.push(game.clone());
+            self.save();
+        }
+    }
+
+    pub fn remove(&mut self, game: &Game) {
+        self.games
+            .retain(|favorite| favorite.as_path() != game.as_path());
+        self.missing.retain(|path| path != game.as_path());
+        self.save();
+    }
+
+    /// Informs the favorites task so it can be persisted
+    fn save(&self) {
+        let Some(sender) = FAVORITES_SENDER.get() else {
+            tracing::error!("Favorites changed before the favorites task started");
+            return;
+        };
+        // Only fills up if favorites are toggled faster than they can be saved
+        if let Err(err) = sender.try_send(self.clone()) {
+            tracing::error!("Failed to queue favorites update: {err:?}");
+        }
+    }
+}
+
+/// Persist favorites updates from the ui
+pub async fn task(mut recv: mpsc::Receiver<Favorites>) {
+    while let Some(favorites) = recv.recv().await {
+        if let Err(err) = persist::write(
+            layout().data(FAVORITES_FILE),
+            serde_json::to_vec_pretty(&favorites).unwrap(),
+        )
+        .await
+        {
+            tracing::error!("Failed to save favorites: {err:?}")
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! BIOS files cores look for in the shared BIOS dir, and whether they're there
+
+use std::{io, path::Path};
+
+use futures_util::future::join_all;
+use layout::layout;
+
+use crate::asyncify;
+
+use super::Console;
+
+/// Save ram files cores write themselves, moved over by `migrate`
+const SAVE_EXTENSIONS: &[&str] = &["srm", "sav", "rtc", "eep", "fla", "sra", "mpk"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BiosFile {
+    pub console: Console,
+    pub name: &'static str,
+    pub md5: &'static str,
+    /// Games mostly run without optional ones, with less accuracy
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BiosStatus {
+    Ok,
+    Missing,
+    /// There is a file, but it's a bad dump or the wrong one
+    WrongChecksum,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BiosCheck {
+    pub file: BiosFile,
+    pub status: BiosStatus,
+}
+
+impl BiosCheck {
+    /// A required file that isn't usable
+    pub fn problem(&self) -> bool {
+        self.file.required && self.status != BiosStatus::Ok
+    }
+}
+
+/// BIOS files for every console with a core that uses one
+pub fn files() -> Vec<BiosFile> {
+    vec![
+        BiosFile {
+            console: Console::GBA,
+            name: "gba_bios.bin",
+            md5: "a860e8c0b6d573d191e4ec7db1b1e4f6",
+            // gpsp falls back to its own HLE BIOS, a few games need the real one
+            required: false,
+        },
+        BiosFile {
+            console: Console::GB,
+            name: "gb_bios.bin",
+            md5: "32fbbd84168d3482956eb3c5051637f5",
+            required: false,
+        },
+        BiosFile {
+            console: Console::GBC,
+            name: "gbc_bios.bin",
+            md5: "dbfce9db9deaa2567f6a84fde55f9680",
+            required: false,
+        },
+        BiosFile {
+            console: Console::NES,
+            name: "disksys.rom",
+            md5: "ca30b50f880eb660a320674ed365ef7a",
+            required: false,
+        },
+    ]
+}
+
+/// Checks every BIOS file in `files`
+pub async fn check() -> Vec<BiosCheck> {
+    join_all(files().into_iter().map(|file| async move {
+        let path = layout().bios().join(file.name);
+        let status = match md5(&path).await {
+            Ok(md5) if md5 == file.md5 => BiosStatus::Ok,
+            Ok(md5) => {
+                tracing::warn!("{} has md5 {md5}, expected {}", path.display(), file.md5);
+                BiosStatus::WrongChecksum
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BiosStatus::Missing,
+            Err(err) => {
+                tracing::error!("Failed to read {}: {err:?}", path.display());
+                BiosStatus::Missing
+            }
+        };
+
+        BiosCheck { file, status }
+    }))
+    .await
+}
+
+/// Moves BIOS and save files out of the old `Saves/<core>` dirs, which cores used
+/// as both their system and save dir before there was a shared BIOS dir
+pub async fn migrate() {
+    if let Err(err) = asyncify(migrate_sync).await {
+        tracing::error!("Failed to migrate old core dirs: {err:?}");
+    }
+}
+
+fn migrate_sync() -> io::Result<()> {
+    let bios_names: Vec<&str> = files().iter().map(|file| file.name).collect();
+
+    let cores = match std::fs::read_dir(layout().all_saves()) {
+        Ok(cores) => cores,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    for core in cores {
+        let core = core?;
+        if !core.file_type()?.is_dir() {
+            continue;
+        }
+        let core_name = core.file_name().to_string_lossy().into_owned();
+
+        for file in std::fs::read_dir(core.path())? {
+            let file = file?;
+            if !file.file_type()?.is_file() {
+                continue;
+            }
+            let name = file.file_name().to_string_lossy().into_owned();
+            let extension = Path::new(&name)
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(str::to_lowercase);
+
+            // Anything else may be the core's own files, leave those be
+            let dest = if bios_names.contains(&name.as_str()) {
+                layout().bios().join(&name)
+            } else if extension.map_or(false, |extension| {
+                SAVE_EXTENSIONS.contains(&extension.as_str())
+            }) {
+                layout().saves(&core_name).join(&name)
+            } else {
+                continue;
+            };
+
+            if dest.exists() {
+                tracing::warn!(
+                    "Not moving {}, {} already exists",
+                    file.path().display(),
+                    dest.display()
+                );
+                continue;
+            }
+
+            tracing::info!("Moving {} to {}", file.path().display(), dest.display());
+            std::fs::create_dir_all(dest.parent().unwrap())?;
+            std::fs::rename(file.path(), &dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Hex md5 of the file at `path`, BIOS files are small so it's read all at once
+async fn md5(path: &Path) -> io::Result<String> {
+    let path = path.to_path_buf();
+    asyncify(move || {
+        let bytes = std::fs::read(path)?;
+        Ok(format!("{:x}", md5::compute(bytes)))
+    })
+    .await
+}
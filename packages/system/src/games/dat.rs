@@ -0,0 +1,310 @@
+//! No-Intro / Redump DAT files (Logiqx xml) used to look up canonical titles by crc32
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use parking_lot::Mutex;
+use quick_xml::events::{BytesStart, Event};
+use serde::{Deserialize, Serialize};
+
+use crate::asyncify;
+
+#[derive(Debug, Default)]
+pub struct Dat {
+    /// crc32 of rom -> name of game
+    names: HashMap<u32, String>,
+}
+
+impl Dat {
+    /// Returns `None` if there is no DAT file at `path`
+    pub async fn load(path: impl AsRef<Path>) -> io::Result<Option<Self>> {
+        let path = path.as_ref();
+        let xml = match tokio::fs::read_to_string(path).await {
+            Ok(xml) => xml,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        tracing::debug!("Parsing DAT: {}", path.display());
+        asyncify(move || Self::parse(&xml)).await.map(Some)
+    }
+
+    pub fn parse(xml: &str) -> io::Result<Self> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut dat = Self::default();
+        let mut current_game: Option<String> = None;
+
+        loop {
+            match reader.read_event().map_err(xml_err)? {
+                Event::Start(tag) if tag.name().as_ref() == b"game" => {
+                    current_game = attribute(&tag, b"name")?;
+                }
+                Event::End(tag) if tag.name().as_ref() == b"game" => {
+                    current_game = None;
+                }
+                Event::Start(tag) | Event::Empty(tag) if tag.name().as_ref() == b"rom" => {
+                    let (Some(name), Some(crc)) = (current_game.as_ref(), attribute(&tag, b"crc")?)
+                    else {
+                        continue;
+                    };
+
+                    match u32::from_str_radix(&crc, 16) {
+                        Ok(crc) => {
+                            dat.names.insert(crc, name.clone());
+                        }
+                        Err(_) => tracing::error!("Invalid crc in DAT for {name}: {crc}"),
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(dat)
+    }
+
+    pub fn name(&self, crc: u32) -> Option<&str> {
+        self.names.get(&crc).map(|name| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Computes the crc32 of the file at `path` on the blocking thread pool
+pub async fn crc32(path: impl AsRef<Path>) -> io::Result<u32> {
+    let path = path.as_ref().to_path_buf();
+    asyncify(move || {
+        use std::io::Read;
+
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = crc32fast::Hasher::new();
+        let mut buf = vec![0u8; 64 * 1024];
+
+        loop {
+            let bytes_read = file.read(&mut buf)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buf[..bytes_read]);
+        }
+
+        Ok(hasher.finalize())
+    })
+    .await
+}
+
+/// Rom crcs from the last refresh, so only new or changed roms have to be read in full
+#[derive(Debug, Default)]
+pub struct CrcCache {
+    old: HashMap<PathBuf, CachedCrc>,
+    /// Roms seen this refresh, this is what gets saved so removed roms drop out
+    new: Mutex<HashMap<PathBuf, CachedCrc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedCrc {
+    size: u64,
+    /// Since the unix epoch
+    modified: Duration,
+    crc: u32,
+}
+
+impl CrcCache {
+    /// Starts empty if there is no cache or it can't be read
+    pub async fn load(path: impl Into<PathBuf>) -> Self {
+        let old = match persist::read_with(path, |bytes| {
+            serde_json::from_slice::<HashMap<PathBuf, CachedCrc>>(&bytes)
+        })
+        .await
+        {
+            Ok(old) => old,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => {
+                tracing::error!("Failed to read crc cache, starting empty: {err:?}");
+                HashMap::new()
+            }
+        };
+
+        Self {
+            old,
+            new: Mutex::default(),
+        }
+    }
+
+    /// crc32 of the rom at `path`, only read if its size or modified time changed
+    pub async fn crc32(&self, path: &Path) -> io::Result<u32> {
+        let meta = tokio::fs::metadata(path).await?;
+        let size = meta.len();
+        let modified = meta
+            .modified()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let crc = match self.old.get(path) {
+            Some(cached) if cached.size == size && cached.modified == modified => cached.crc,
+            _ => crc32(path).await?,
+        };
+
+        self.new.lock().insert(
+            path.to_path_buf(),
+            CachedCrc {
+                size,
+                modified,
+                crc,
+            },
+        );
+        Ok(crc)
+    }
+
+    /// Writes the crcs seen since `load`, skipped if nothing changed
+    pub async fn save(&self, path: impl Into<PathBuf>) -> io::Result<()> {
+        let new = self.new.lock().clone();
+        if new == self.old {
+            return Ok(());
+        }
+        persist::write(path, serde_json::to_vec(&new).unwrap()).await
+    }
+}
+
+fn attribute(tag: &BytesStart, key: &[u8]) -> io::Result<Option<String>> {
+    for attr in tag.attributes() {
+        let attr = attr.map_err(xml_err)?;
+        if attr.key.as_ref() == key {
+            return Ok(Some(attr.unescape_value().map_err(xml_err)?.into_owned()));
+        }
+    }
+
+    Ok(None)
+}
+
+fn xml_err(err: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAT: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd">
+<datafile>
+	<header>
+		<name>Nintendo - Game Boy Advance</name>
+		<description>Nintendo - Game Boy Advance</description>
+	</header>
+	<game name="Legend of Zelda, The - The Minish Cap (USA)">
+		<description>Legend of Zelda, The - The Minish Cap (USA)</description>
+		<rom name="Legend of Zelda, The - The Minish Cap (USA).gba" size="16777216" crc="C6C9D4A0" md5="f2e4b5bd9ea5e9b2c1b5b4a1c3d2e1f0"/>
+	</game>
+	<game name="Mario &amp; Luigi - Superstar Saga (USA)">
+		<description>Mario &amp; Luigi - Superstar Saga (USA)</description>
+		<rom name="Mario &amp; Luigi - Superstar Saga (USA).gba" size="8388608" crc="a2a8ba30"/>
+	</game>
+	<game name="No Crc (USA)">
+		<rom name="No Crc (USA).gba" size="4"/>
+	</game>
+	<game name="Bad Crc (USA)">
+		<rom name="Bad Crc (USA).gba" size="4" crc="nothex"/>
+	</game>
+	<game name="Multi Rom (USA)">
+		<rom name="Multi Rom (USA) (Track 1).bin" size="4" crc="00000001"></rom>
+		<rom name="Multi Rom (USA) (Track 2).bin" size="4" crc="00000002"></rom>
+	</game>
+	<rom name="Outside a game.gba" size="4" crc="00000003"/>
+</datafile>
+"#;
+
+    #[test]
+    fn parse() {
+        let dat = Dat::parse(DAT).unwrap();
+
+        for (crc, name) in [
+            (
+                0xC6C9D4A0,
+                Some("Legend of Zelda, The - The Minish Cap (USA)"),
+            ),
+            // Lowercase crcs and escaped names
+            (0xA2A8BA30, Some("Mario & Luigi - Superstar Saga (USA)")),
+            (0x00000001, Some("Multi Rom (USA)")),
+            (0x00000002, Some("Multi Rom (USA)")),
+            (0x00000003, None),
+            (0xDEADBEEF, None),
+        ] {
+            assert_eq!(dat.name(crc), name, "{crc:08X}");
+        }
+        assert_eq!(dat.len(), 4);
+    }
+
+    #[test]
+    fn parse_empty() {
+        let dat = Dat::parse("<datafile></datafile>").unwrap();
+        assert!(dat.is_empty());
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let err = Dat::parse(r#"<datafile><game name="Broken></datafile>"#).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn load() {
+        let dir = std::env::temp_dir().join(format!("dat-test-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+
+        assert!(Dat::load(dir.join("missing.dat")).await.unwrap().is_none());
+
+        let path = dir.join("gba.dat");
+        tokio::fs::write(&path, DAT).await.unwrap();
+        let dat = Dat::load(&path).await.unwrap().unwrap();
+        assert_eq!(dat.len(), 4);
+
+        // Standard crc32 check value
+        let rom = dir.join("check.bin");
+        tokio::fs::write(&rom, b"123456789").await.unwrap();
+        assert_eq!(crc32(&rom).await.unwrap(), 0xCBF43926);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn crc_cache() {
+        let dir = std::env::temp_dir().join(format!("crc-cache-test-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let cache_path = dir.join("crcs.json");
+        let rom = dir.join("check.bin");
+        tokio::fs::write(&rom, b"123456789").await.unwrap();
+
+        let crcs = CrcCache::load(&cache_path).await;
+        assert_eq!(crcs.crc32(&rom).await.unwrap(), 0xCBF43926);
+        crcs.save(&cache_path).await.unwrap();
+
+        // A cached crc is trusted while the size and modified time match
+        let mut crcs = CrcCache::load(&cache_path).await;
+        crcs.old.get_mut(&rom).unwrap().crc = 1;
+        assert_eq!(crcs.crc32(&rom).await.unwrap(), 1);
+
+        // and recomputed once they don't
+        crcs.old.get_mut(&rom).unwrap().size += 1;
+        assert_eq!(crcs.crc32(&rom).await.unwrap(), 0xCBF43926);
+
+        // Roms that weren't looked at this time are dropped
+        crcs.new.lock().clear();
+        crcs.save(&cache_path).await.unwrap();
+        assert!(CrcCache::load(&cache_path).await.old.is_empty());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
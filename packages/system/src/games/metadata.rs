@@ -0,0 +1,482 @@
+use serde::{Deserialize, Serialize};
+
+/// Leading articles which are ignored when sorting and which No-Intro moves to the end of a title
+const ARTICLES: &[&str] = &["The", "A", "An"];
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Region {
+    World,
+    USA,
+    Europe,
+    Japan,
+    Asia,
+    Australia,
+    Brazil,
+    Canada,
+    China,
+    France,
+    Germany,
+    HongKong,
+    Italy,
+    Korea,
+    Netherlands,
+    Spain,
+    Sweden,
+    Taiwan,
+    UK,
+}
+
+impl Region {
+    pub fn from_str(str: impl AsRef<str>) -> Option<Self> {
+        let str = str.as_ref();
+        match str {
+            "World" => Some(Self::World),
+            "USA" => Some(Self::USA),
+            "Europe" => Some(Self::Europe),
+            "Japan" => Some(Self::Japan),
+            "Asia" => Some(Self::Asia),
+            "Australia" => Some(Self::Australia),
+            "Brazil" => Some(Self::Brazil),
+            "Canada" => Some(Self::Canada),
+            "China" => Some(Self::China),
+            "France" => Some(Self::France),
+            "Germany" => Some(Self::Germany),
+            "Hong Kong" => Some(Self::HongKong),
+            "Italy" => Some(Self::Italy),
+            "Korea" => Some(Self::Korea),
+            "Netherlands" => Some(Self::Netherlands),
+            "Spain" => Some(Self::Spain),
+            "Sweden" => Some(Self::Sweden),
+            "Taiwan" => Some(Self::Taiwan),
+            "UK" => Some(Self::UK),
+            _ => None,
+        }
+    }
+
+    /// Short code to display next to a title
+    pub fn badge(&self) -> &str {
+        match self {
+            Self::World => "W",
+            Self::USA => "US",
+            Self::Europe => "EU",
+            Self::Japan => "JP",
+            Self::Asia => "AS",
+            Self::Australia => "AU",
+            Self::Brazil => "BR",
+            Self::Canada => "CA",
+            Self::China => "CN",
+            Self::France => "FR",
+            Self::Germany => "DE",
+            Self::HongKong => "HK",
+            Self::Italy => "IT",
+            Self::Korea => "KR",
+            Self::Netherlands => "NL",
+            Self::Spain => "ES",
+            Self::Sweden => "SE",
+            Self::Taiwan => "TW",
+            Self::UK => "UK",
+        }
+    }
+}
+
+/// Info parsed out of a No-Intro / Redump style name
+/// such as `Legend of Zelda, The - The Minish Cap (USA) (Rev 1)`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// Title with tags removed and articles moved to the front
+    title: String,
+    regions: Vec<Region>,
+    /// Language codes such as `En` or `Fr`
+    languages: Vec<String>,
+    revision: Option<String>,
+    /// Any other tags such as `Beta`, `Proto` or dump flags like `!`
+    tags: Vec<String>,
+    sort_key: String,
+}
+
+impl Metadata {
+    pub fn parse(name: &str) -> Self {
+        let (title, tags) = match name.find(|c| c == '(' || c == '[') {
+            Some(idx) if !name[..idx].trim().is_empty() => (name[..idx].trim(), &name[idx..]),
+            _ => (name.trim(), ""),
+        };
+        let title = move_article(title);
+
+        let mut metadata = Self {
+            sort_key: sort_key(&title),
+            title,
+            regions: Vec::new(),
+            languages: Vec::new(),
+            revision: None,
+            tags: Vec::new(),
+        };
+
+        for tag in split_tags(tags) {
+            if let Some(regions) = tag
+                .split(", ")
+                .map(Region::from_str)
+                .collect::<Option<Vec<_>>>()
+            {
+                metadata.regions.extend(regions);
+            } else if tag.split(',').all(is_language) {
+                metadata
+                    .languages
+                    .extend(tag.split(',').map(|lang| lang.to_string()));
+            } else if let Some(revision) = tag.strip_prefix("Rev ") {
+                metadata.revision = Some(revision.into());
+            } else if tag.starts_with('v') && tag[1..].starts_with(|c: char| c.is_ascii_digit()) {
+                metadata.revision = Some(tag.into());
+            } else if !tag.is_empty() {
+                metadata.tags.push(tag.into());
+            }
+        }
+
+        metadata
+    }
+
+    #[inline]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    #[inline]
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    #[inline]
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    #[inline]
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    #[inline]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Lowercase title without leading articles, use this to sort games
+    #[inline]
+    pub fn sort_key(&self) -> &str {
+        &self.sort_key
+    }
+
+    /// Letter the game is grouped under for alphabet jumps, `#` for anything not starting with one
+    pub fn letter(&self) -> char {
+        match self.sort_key.chars().next() {
+            Some(c) if c.is_alphabetic() => c.to_uppercase().next().unwrap_or(c),
+            _ => '#',
+        }
+    }
+
+    /// Region badges joined together, ex: `US/EU`
+    pub fn region_badge(&self) -> Option<String> {
+        if self.regions.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.regions
+                .iter()
+                .map(|region| region.badge())
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+}
+
+/// Turns `Legend of Zelda, The - The Minish Cap` into `The Legend of Zelda - The Minish Cap`
+fn move_article(title: &str) -> String {
+    let (main, sub) = match title.split_once(" - ") {
+        Some((main, sub)) => (main, Some(sub)),
+        None => (title, None),
+    };
+
+    let main = ARTICLES
+        .iter()
+        .find_map(|article| {
+            main.strip_suffix(article)
+                .and_then(|rest| rest.strip_suffix(", "))
+                .map(|rest| format!("{article} {rest}"))
+        })
+        .unwrap_or_else(|| main.to_string());
+
+    match sub {
+        Some(sub) => format!("{main} - {sub}"),
+        None => main,
+    }
+}
+
+fn sort_key(title: &str) -> String {
+    let lower = title.to_lowercase();
+    let without_article = ARTICLES
+        .iter()
+        .find_map(|article| {
+            lower
+                .strip_prefix(&*article.to_lowercase())
+                .and_then(|rest| rest.strip_prefix(' '))
+        })
+        .unwrap_or(&lower);
+
+    let stripped: String = without_article
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect();
+    // Dropping punctuation like ` - ` leaves runs of spaces, which would sort before letters
+    stripped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Returns the contents of each `(...)` and `[...]` group
+fn split_tags(tags: &str) -> impl Iterator<Item = &str> {
+    let mut rest = tags;
+    std::iter::from_fn(move || {
+        let start = rest.find(|c| c == '(' || c == '[')?;
+        let close = if rest[start..].starts_with('(') {
+            ')'
+        } else {
+            ']'
+        };
+        let inner = &rest[start + 1..];
+        match inner.find(close) {
+            Some(end) => {
+                rest = &inner[end + 1..];
+                Some(inner[..end].trim())
+            }
+            None => {
+                // Unclosed group, just take the rest
+                rest = "";
+                Some(inner.trim())
+            }
+        }
+    })
+}
+
+/// Language codes look like `En`, `Ja` or `Zh-Hant`
+fn is_language(code: &str) -> bool {
+    let code = code.split('-').next().unwrap_or_default();
+    let mut chars = code.chars();
+    matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some(first), Some(second), None) if first.is_ascii_uppercase() && second.is_ascii_lowercase()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        // Name, title, regions, languages, revision, tags
+        let cases: &[(&str, &str, &[Region], &[&str], Option<&str>, &[&str])] = &[
+            (
+                "Legend of Zelda, The - The Minish Cap (USA)",
+                "The Legend of Zelda - The Minish Cap",
+                &[Region::USA],
+                &[],
+                None,
+                &[],
+            ),
+            (
+                "Pokemon - Emerald Version (USA, Europe)",
+                "Pokemon - Emerald Version",
+                &[Region::USA, Region::Europe],
+                &[],
+                None,
+                &[],
+            ),
+            (
+                "Advance Wars (USA) (Rev 1)",
+                "Advance Wars",
+                &[Region::USA],
+                &[],
+                Some("1"),
+                &[],
+            ),
+            (
+                "Rayman Advance (USA, Europe) (En,Fr,De,Es,It) (Rev 1)",
+                "Rayman Advance",
+                &[Region::USA, Region::Europe],
+                &["En", "Fr", "De", "Es", "It"],
+                Some("1"),
+                &[],
+            ),
+            (
+                "Mother 3 (Japan)",
+                "Mother 3",
+                &[Region::Japan],
+                &[],
+                None,
+                &[],
+            ),
+            (
+                "Pokemon - Pinball (USA) (Rumble Version) (SGB Enhanced)",
+                "Pokemon - Pinball",
+                &[Region::USA],
+                &[],
+                None,
+                &["Rumble Version", "SGB Enhanced"],
+            ),
+            (
+                "Tetris (World) (Rev A)",
+                "Tetris",
+                &[Region::World],
+                &[],
+                Some("A"),
+                &[],
+            ),
+            (
+                "Kirby - Nightmare in Dream Land (USA) (Beta)",
+                "Kirby - Nightmare in Dream Land",
+                &[Region::USA],
+                &[],
+                None,
+                &["Beta"],
+            ),
+            (
+                "Pokemon Mystery Dungeon - Red Rescue Team (USA, Australia)",
+                "Pokemon Mystery Dungeon - Red Rescue Team",
+                &[Region::USA, Region::Australia],
+                &[],
+                None,
+                &[],
+            ),
+            (
+                "Shin Megami Tensei (Japan) (Zh-Hant)",
+                "Shin Megami Tensei",
+                &[Region::Japan],
+                &["Zh-Hant"],
+                None,
+                &[],
+            ),
+            // Redump style version
+            (
+                "Crash Bandicoot (Europe) (v1.1)",
+                "Crash Bandicoot",
+                &[Region::Europe],
+                &[],
+                Some("v1.1"),
+                &[],
+            ),
+            // GoodTools style dump flags
+            (
+                "Super Mario Land (W) (V1.1) [!]",
+                "Super Mario Land",
+                &[],
+                &[],
+                None,
+                &["W", "V1.1", "!"],
+            ),
+            // Plain file names
+            ("Tetris", "Tetris", &[], &[], None, &[]),
+            ("  Tetris  ", "Tetris", &[], &[], None, &[]),
+            // Unclosed group
+            (
+                "Metroid Fusion (USA",
+                "Metroid Fusion",
+                &[Region::USA],
+                &[],
+                None,
+                &[],
+            ),
+            // Nothing before the tags, keep the whole name
+            ("(USA)", "(USA)", &[], &[], None, &[]),
+        ];
+
+        for (name, title, regions, languages, revision, tags) in cases {
+            let metadata = Metadata::parse(name);
+            assert_eq!(metadata.title(), *title, "{name}");
+            assert_eq!(metadata.regions(), *regions, "{name}");
+            assert_eq!(metadata.languages(), *languages, "{name}");
+            assert_eq!(metadata.revision(), *revision, "{name}");
+            assert_eq!(metadata.tags(), *tags, "{name}");
+        }
+    }
+
+    #[test]
+    fn move_articles() {
+        for (title, moved) in [
+            ("Sims 2, The", "The Sims 2"),
+            (
+                "Legend of Zelda, The - A Link to the Past",
+                "The Legend of Zelda - A Link to the Past",
+            ),
+            ("Bug's Life, A", "A Bug's Life"),
+            ("Adventure, An", "An Adventure"),
+            // Only a trailing article is moved
+            ("Theme Park", "Theme Park"),
+            (
+                "Mario & Luigi - Superstar Saga",
+                "Mario & Luigi - Superstar Saga",
+            ),
+        ] {
+            assert_eq!(move_article(title), moved);
+        }
+    }
+
+    #[test]
+    fn sort_keys() {
+        for (name, sort_key, letter) in [
+            (
+                "Legend of Zelda, The - The Minish Cap (USA)",
+                "legend of zelda the minish cap",
+                'L',
+            ),
+            ("Bug's Life, A (USA)", "bugs life", 'B'),
+            ("Theme Park (Europe)", "theme park", 'T'),
+            (
+                "Mario & Luigi - Superstar Saga (USA)",
+                "mario luigi superstar saga",
+                'M',
+            ),
+            (
+                "007 - Everything or Nothing (USA, Europe)",
+                "007 everything or nothing",
+                '#',
+            ),
+            ("Ōkami (Japan)", "ōkami", 'Ō'),
+        ] {
+            let metadata = Metadata::parse(name);
+            assert_eq!(metadata.sort_key(), sort_key, "{name}");
+            assert_eq!(metadata.letter(), letter, "{name}");
+        }
+
+        let red = Metadata::parse("Pokemon - Red Version (USA, Europe)");
+        let blue = Metadata::parse("Pokemon Blue (USA)");
+        assert!(blue.sort_key() < red.sort_key());
+    }
+
+    #[test]
+    fn region_badge() {
+        for (name, badge) in [
+            ("Golden Sun (USA, Europe)", Some("US/EU")),
+            ("Mother 3 (Japan)", Some("JP")),
+            ("Tetris (World) (Rev A)", Some("W")),
+            ("Tetris", None),
+        ] {
+            assert_eq!(
+                Metadata::parse(name).region_badge().as_deref(),
+                badge,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn languages() {
+        for (code, language) in [
+            ("En", true),
+            ("Zh-Hant", true),
+            ("EN", false),
+            ("E", false),
+            ("Eng", false),
+            ("Beta", false),
+        ] {
+            assert_eq!(is_language(code), language, "{code}");
+        }
+    }
+}
@@ -1,23 +1,34 @@
+pub mod bios;
 pub mod console;
+pub mod dat;
+pub mod metadata;
+pub mod search;
 
 pub use console::Console;
+use dat::{CrcCache, Dat};
 use fixed_map::Map;
 use futures_util::{future::join_all, TryStreamExt};
+use layout::layout;
+pub use metadata::{Metadata, Region};
 use serde::{Deserialize, Serialize};
-use tokio::io::AsyncWriteExt;
 use tokio_stream::wrappers::ReadDirStream;
 
 use std::{
     io,
     path::{Path, PathBuf},
     sync::Arc,
+    time::SystemTime,
 };
 
+/// Rom crcs for matching against DATs, see `CrcCache`
+const CRC_CACHE_FILE: &str = ".crc_cache.json";
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Game {
     path: PathBuf,
     console: Console,
     core: String,
+    metadata: Metadata,
 }
 
 impl Game {
@@ -32,14 +43,27 @@ impl Game {
         }
 
         Some(Self {
+            metadata: Metadata::parse(path.file_stem()?.to_str()?),
             path: path.to_path_buf(),
             core: console.default_core().into(),
             console,
         })
     }
 
+    /// Replaces metadata parsed from the file name with the DAT's canonical name, if the crc matches
+    async fn apply_dat(&mut self, dat: &Dat, crcs: &CrcCache) {
+        match crcs.crc32(&self.path).await {
+            Ok(crc) => {
+                if let Some(name) = dat.name(crc) {
+                    self.metadata = Metadata::parse(name);
+                }
+            }
+            Err(err) => tracing::error!("Failed to crc {}: {err:?}", self.path.display()),
+        }
+    }
+
     /// Checks that `path` points to an actual file
-    async fn exists(&self) -> bool {
+    pub async fn exists(&self) -> bool {
         match tokio::fs::try_exists(&self.path).await {
             Ok(true) => {
                 // File exists
@@ -52,6 +76,43 @@ impl Game {
         }
     }
 
+    /// Finds an image to show for this game, box art from `Imgs/<console>/<name>.png`
+    /// or the screenshot of the most recent save state as a fallback
+    pub async fn thumbnail_path(&self) -> Option<PathBuf> {
+        let box_art = layout()
+            .imgs(self.console.name())
+            .join(format!("{}.png", self.full_name()));
+        if let Ok(true) = tokio::fs::try_exists(&box_art).await {
+            return Some(box_art);
+        }
+
+        // Save states are written as `<name>-<slot>.sav` with a `.sav.png` screenshot next to them
+        let prefix = format!("{}-", self.full_name());
+        let mut dir = tokio::fs::read_dir(layout().saves(&self.core)).await.ok()?;
+        let mut latest: Option<(SystemTime, PathBuf)> = None;
+
+        while let Ok(Some(file)) = dir.next_entry().await {
+            let path = file.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            if !name.starts_with(&prefix) || !name.ends_with(".sav.png") {
+                continue;
+            }
+
+            let Ok(modified) = file.metadata().await.and_then(|meta| meta.modified()) else {
+                continue;
+            };
+
+            if latest.as_ref().map_or(true, |(time, _)| modified > *time) {
+                latest = Some((modified, path));
+            }
+        }
+
+        latest.map(|(_, path)| path)
+    }
+
     #[inline]
     pub fn as_path(&self) -> &Path {
         &self.path
@@ -63,6 +124,17 @@ impl Game {
         self.path.file_stem().unwrap().to_str().unwrap()
     }
 
+    /// Clean title without region/revision tags, prefer this for display
+    #[inline]
+    pub fn title(&self) -> &str {
+        self.metadata.title()
+    }
+
+    #[inline]
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
     #[inline]
     pub fn console(&self) -> &Console {
         &self.console
@@ -94,9 +166,9 @@ pub async fn refresh_game_cache() -> io::Result<GameCache> {
     // First, make sure the folder for each console exist
     let create_dirs = Console::iter()
         .map(|console| {
-            tokio::spawn(async move {
-                tokio::fs::create_dir_all(format!("/mnt/SDCARD/Games/{}", console.name())).await
-            })
+            tokio::spawn(
+                async move { tokio::fs::create_dir_all(layout().games(console.name())).await },
+            )
         })
         .collect::<Vec<_>>();
 
@@ -105,14 +177,17 @@ pub async fn refresh_game_cache() -> io::Result<GameCache> {
         task.unwrap()?;
     }
 
+    // Reading every rom on boot is slow, so crcs are only redone for changed roms
+    let crcs = Arc::new(CrcCache::load(layout().data(CRC_CACHE_FILE)).await);
+
     tracing::debug!("Reading games");
     // Read all the files in each game dir and turn them into `Game` structs
     let get_games = Console::iter()
         .map(|console| {
+            let crcs = crcs.clone();
             tokio::spawn(async move {
-                let mut dir = ReadDirStream::new(
-                    tokio::fs::read_dir(format!("/mnt/SDCARD/Games/{}", console.name())).await?,
-                );
+                let mut dir =
+                    ReadDirStream::new(tokio::fs::read_dir(layout().games(console.name())).await?);
                 let mut games = Vec::<Game>::new();
 
                 tracing::debug!("Reading console: {}", console.name());
@@ -131,6 +206,22 @@ pub async fn refresh_game_cache() -> io::Result<GameCache> {
                     }
                 }
 
+                // Use canonical names from a DAT if the user provided one for this console
+                match Dat::load(layout().dat(console.name())).await {
+                    Ok(Some(dat)) => {
+                        tracing::debug!("Matching against DAT with {} entries", dat.len());
+                        for game in games.iter_mut() {
+                            game.apply_dat(&dat, &crcs).await;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        tracing::error!("Failed to load DAT for {}: {err:?}", console.name())
+                    }
+                }
+
+                games.sort_by(|a, b| a.metadata.sort_key().cmp(b.metadata.sort_key()));
+
                 tracing::debug!("Done with console: {}", console.name());
                 Ok::<Vec<Game>, io::Error>(games)
             })
@@ -157,16 +248,17 @@ pub async fn refresh_game_cache() -> io::Result<GameCache> {
         }
     }
 
+    if let Err(err) = crcs.save(layout().data(CRC_CACHE_FILE)).await {
+        tracing::error!("Failed to save crc cache: {err:?}");
+    }
+
     tracing::debug!("Writing games");
     // Serialize using intermediate because Arc<[Game]> isn't Serialize
-    tokio::fs::OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .create(true)
-        .open(".game_cache.json")
-        .await?
-        .write_all(&serde_json::to_vec(&intermediate).unwrap())
-        .await?;
+    persist::write(
+        layout().data(".game_cache.json"),
+        serde_json::to_vec(&intermediate).unwrap(),
+    )
+    .await?;
 
     Ok(intermediate_to_final(intermediate))
 }
@@ -176,8 +268,11 @@ pub(crate) async fn init() -> io::Result<GameCache> {
     // Always just get all games on startup
     let games = refresh_game_cache().await?;
 
+    // Cores all share one dir for BIOS files
+    tokio::fs::create_dir_all(layout().bios()).await?;
+
     // Make sure there is a save dir for each core
-    let mut dir = tokio::fs::read_dir("/mnt/SDCARD/Cores").await?;
+    let mut dir = tokio::fs::read_dir(layout().cores()).await?;
 
     while let Some(file) = dir.next_entry().await? {
         if file.file_type().await?.is_file() {
@@ -189,9 +284,7 @@ pub(crate) async fn init() -> io::Result<GameCache> {
                 .unwrap()
                 .trim_end_matches("_libretro.so");
 
-            if let Err(err) =
-                tokio::fs::create_dir_all(format!("/mnt/SDCARD/Saves/{core_name}/saves")).await
-            {
+            if let Err(err) = tokio::fs::create_dir_all(layout().saves(core_name)).await {
                 if err.kind() != io::ErrorKind::AlreadyExists {
                     return Err(err);
                 }
@@ -0,0 +1,152 @@
+use crate::{favorites::Favorites, history};
+
+use super::{Console, Game, GameCache};
+
+/// Narrows down which games are searched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Filter {
+    #[default]
+    All,
+    Console(Console),
+    Favorites,
+    /// Games from the play history, most recent first
+    Recent,
+}
+
+impl Filter {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::All => "All",
+            Self::Console(console) => console.name(),
+            Self::Favorites => "Favorites",
+            Self::Recent => "Recently played",
+        }
+    }
+
+    /// Cycles All -> each console -> Favorites -> Recent -> All
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Console::iter()
+                .next()
+                .map_or(Self::Favorites, Self::Console),
+            Self::Console(console) => Console::iter()
+                .skip_while(|other| *other != console)
+                .nth(1)
+                .map_or(Self::Favorites, Self::Console),
+            Self::Favorites => Self::Recent,
+            Self::Recent => Self::All,
+        }
+    }
+}
+
+/// Finds games matching `query` within `filter`, best matches first.
+/// An empty query returns every game in the filter in its usual order
+pub fn search(games: &GameCache, favorites: &Favorites, query: &str, filter: Filter) -> Vec<Game> {
+    let candidates: Vec<Game> = match filter {
+        Filter::All => Console::iter()
+            .filter_map(|console| games.get(console))
+            .flat_map(|games| games.iter().cloned())
+            .collect(),
+        Filter::Console(console) => games
+            .get(console)
+            .map(|games| games.to_vec())
+            .unwrap_or_default(),
+        Filter::Favorites => favorites.games().to_vec(),
+        Filter::Recent => history::entries()
+            .into_iter()
+            .map(|entry| entry.game)
+            .collect(),
+    };
+
+    let query = query.trim();
+    if query.is_empty() {
+        return candidates;
+    }
+
+    let mut matches: Vec<(u32, Game)> = candidates
+        .into_iter()
+        .filter_map(|game| Some((fuzzy_score(query, game.full_name())?, game)))
+        .collect();
+
+    // Stable sort keeps the filter's order between equal scores
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+    matches.into_iter().map(|(_, game)| game).collect()
+}
+
+/// Scores how well `query` matches `name`, `None` if the query's characters
+/// don't all appear in order. Runs of consecutive characters and matches at
+/// the start of words score higher
+pub fn fuzzy_score(query: &str, name: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut name_chars = name.chars().flat_map(char::to_lowercase);
+    let mut prev_matched = false;
+    let mut prev_char: Option<char> = None;
+
+    for query_char in query.chars().flat_map(char::to_lowercase) {
+        if query_char.is_whitespace() {
+            // Spaces only separate words in the query
+            continue;
+        }
+
+        loop {
+            let name_char = name_chars.next()?;
+            let word_start = prev_char.map_or(true, |prev| !prev.is_alphanumeric());
+            prev_char = Some(name_char);
+
+            if name_char == query_char {
+                score += 1;
+                if prev_matched {
+                    score += 4;
+                }
+                if word_start {
+                    score += 8;
+                }
+                prev_matched = true;
+                break;
+            }
+
+            prev_matched = false;
+        }
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_matches() {
+        // z starts a word, e and l continue the run
+        assert_eq!(fuzzy_score("zel", "Zelda"), Some(9 + 5 + 5));
+        assert_eq!(fuzzy_score("", "Zelda"), Some(0));
+        assert!(fuzzy_score("ZELDA", "the legend of zelda").is_some());
+        assert!(fuzzy_score("smb", "Super Mario Bros.").is_some());
+    }
+
+    #[test]
+    fn fuzzy_score_misses() {
+        assert_eq!(fuzzy_score("zelda", "Super Mario Bros."), None);
+        // Characters have to appear in order
+        assert_eq!(fuzzy_score("ba", "ab"), None);
+        assert_eq!(fuzzy_score("marioo", "Mario"), None);
+    }
+
+    #[test]
+    fn fuzzy_score_ignores_query_spaces() {
+        assert_eq!(
+            fuzzy_score("super mario", "Super Mario World"),
+            fuzzy_score("supermario", "Super Mario World")
+        );
+    }
+
+    #[test]
+    fn fuzzy_score_ranking() {
+        // Word starts beat matches in the middle of words
+        assert!(fuzzy_score("mb", "Mario Bros") > fuzzy_score("mb", "Gumball"));
+        // Runs beat scattered matches
+        assert!(fuzzy_score("mario", "Mario Kart") > fuzzy_score("mario", "Metal Arms Rio"));
+        assert!(fuzzy_score("tetris", "Tetris") > fuzzy_score("tetris", "The Entire Trials"));
+    }
+}
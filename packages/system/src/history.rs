@@ -0,0 +1,126 @@
+use std::{io, path::PathBuf};
+
+use layout::layout;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::{
+    games::Game,
+    play_time::{self, GameStats},
+};
+
+static HISTORY: Lazy<Mutex<History>> = Lazy::new(|| Mutex::new(History::default()));
+static HISTORY_SENDER: OnceCell<mpsc::Sender<History>> = OnceCell::new();
+
+const HISTORY_FILE: &str = "history.json";
+/// Older entries are dropped past this
+const MAX_ENTRIES: usize = 50;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct History {
+    /// Most recently played first
+    entries: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    pub game: Game,
+    pub core: String,
+}
+
+impl HistoryEntry {
+    /// Screenshot written next to the auto save state by the emulator
+    pub fn auto_save_screenshot(&self) -> PathBuf {
+        layout()
+            .saves(&self.core)
+            .join(format!("{}-auto.sav.png", self.game.full_name()))
+    }
+
+    /// Play time and last played, kept by the play time tracker
+    pub fn stats(&self) -> Option<GameStats> {
+        play_time::game(&self.game)
+    }
+}
+
+/// Loads history from disk, must be called before any games are played
+pub(crate) async fn init() -> io::Result<mpsc::Receiver<History>> {
+    let history = match persist::read_with(layout().data(HISTORY_FILE), |bytes| {
+        serde_json::from_slice::<History>(&bytes)
+    })
+    .await
+    {
+        Ok(history) => history,
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            tracing::error!("Found invalid history file. Starting empty. {err:?}");
+            History::default()
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => History::default(),
+        Err(err) => return Err(err),
+    };
+
+    tracing::debug!("Found {} history entries", history.entries.len());
+    *HISTORY.lock() = history;
+
+    let (send, recv) = mpsc::channel(16);
+    HISTORY_SENDER.set(send).ok();
+
+    Ok(recv)
+}
+
+/// Recently played games, most recent first
+pub fn entries() -> Vec<HistoryEntry> {
+    HISTORY.lock().entries.clone()
+}
+
+/// Moves `game` to the front of the history, adding it if needed
+pub(crate) fn record_start(game: &Game) {
+    let mut history = HISTORY.lock();
+
+    let entry = match history
+        .entries
+        .iter()
+        .position(|entry| entry.game.as_path() == game.as_path())
+    {
+        Some(idx) => {
+            let mut entry = history.entries.remove(idx);
+            entry.game = game.clone();
+            entry.core = game.core().into();
+            entry
+        }
+        None => HistoryEntry {
+            game: game.clone(),
+            core: game.core().into(),
+        },
+    };
+
+    history.entries.insert(0, entry);
+    history.entries.truncate(MAX_ENTRIES);
+    save(&history);
+}
+
+fn save(history: &History) {
+    match HISTORY_SENDER.get() {
+        Some(sender) => {
+            if let Err(err) = sender.try_send(history.clone()) {
+                tracing::error!("Failed to queue history save: {err:?}");
+            }
+        }
+        None => tracing::error!("History saved before init!"),
+    }
+}
+
+/// Persist history updates
+pub async fn task(mut recv: mpsc::Receiver<History>) {
+    while let Some(history) = recv.recv().await {
+        if let Err(err) = persist::write(
+            layout().data(HISTORY_FILE),
+            serde_json::to_vec_pretty(&history).unwrap(),
+        )
+        .await
+        {
+            tracing::error!("Failed to save history: {err:?}")
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Dims, sleeps and finally powers off the device when it isn't being used
+
+use std::time::{Duration, Instant};
+
+use miyoo_mini_hal::{hal, Backend};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tokio::{select, sync::mpsc, sync::Notify};
+
+use crate::{
+    emulator, settings,
+    sleep::{deep_sleep, sleep, sleeping},
+    SystemMessage,
+};
+
+static LAST_INPUT: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+static ACTIVITY: Lazy<Notify> = Lazy::new(Notify::new);
+
+const TICK: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Active,
+    Dimmed,
+    Asleep(Instant),
+}
+
+/// Called for every input event, even in game or asleep
+pub(crate) fn activity() {
+    *LAST_INPUT.lock() = Instant::now();
+    ACTIVITY.notify_one();
+}
+
+pub(crate) async fn task(sender: mpsc::Sender<SystemMessage>) {
+    let mut state = State::Active;
+
+    loop {
+        select! {
+            _ = ACTIVITY.notified() => {}
+            _ = tokio::time::sleep(TICK) => {}
+        }
+
+        let settings = settings::current();
+        let idle_for = LAST_INPUT.lock().elapsed();
+
+        if sleeping() {
+            let asleep_since = match state {
+                State::Asleep(since) => since,
+                // Put to sleep with the power button
+                _ => {
+                    let now = Instant::now();
+                    state = State::Asleep(now);
+                    now
+                }
+            };
+
+            if settings.power_off_timeout != 0
+                && asleep_since.elapsed() >= Duration::from_secs(settings.power_off_timeout as u64)
+            {
+                deep_sleep(&sender).await;
+            }
+            continue;
+        }
+
+        let policy = if emulator::playing() {
+            settings.in_game_idle
+        } else {
+            settings.idle
+        };
+        let dim_after = Duration::from_secs(policy.dim_after as u64);
+        let sleep_after = dim_after + Duration::from_secs(policy.sleep_after as u64);
+
+        // Dimmed before sleeping, or woken with the power button
+        if state != State::Active && (policy.dim_after == 0 || idle_for < dim_after) {
+            if let Err(err) = hal().set_brightness(settings.brightness).await {
+                tracing::error!("Failed to restore brightness: {err:?}");
+            }
+            state = State::Active;
+        }
+
+        // Without dimming, sleep_after counts from the last input
+        if policy.sleep_after != 0 && idle_for >= sleep_after {
+            tracing::debug!("Idle for {idle_for:?}, sleeping...");
+            match sleep().await {
+                Ok(_) => {
+                    state = State::Asleep(Instant::now());
+                    sender.send(SystemMessage::Sleep).await.unwrap();
+                }
+                Err(err) => tracing::error!("Failed to sleep: {err:?}"),
+            }
+        } else if policy.dim_after != 0 && state == State::Active && idle_for >= dim_after {
+            tracing::debug!("Idle for {idle_for:?}, dimming");
+            match hal().dim().await {
+                Ok(_) => state = State::Dimmed,
+                Err(err) => tracing::error!("Failed to dim screen: {err:?}"),
+            }
+        }
+    }
+}
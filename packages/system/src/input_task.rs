@@ -1,20 +1,26 @@
-use std::time::{Duration, Instant};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
-use input::{input_task, Button, ButtonEvent};
-use miyoo_mini_hal::rumble::rumble_for_sync;
+use input::{input_task, key_repeat, Button, ButtonEvent, EventValue, Repeat};
+use ipc::functions::{Overlay, OverlayKind, ShowOverlay, ShowOverlayArgs};
+use miyoo_mini_hal::{hal, Backend};
 use tokio::{
     select,
     sync::{mpsc, oneshot},
 };
 
 use crate::{
-    emulator,
+    emulator, idle, play_time, settings,
     sleep::{sleep, sleeping, wake},
     SystemMessage,
 };
 
 const POWER_OFF_TIME: u64 = 500;
 const MAIN_MENU_TIME: u64 = 500;
+/// Intensity that rumbles for exactly the requested duration
+const DEFAULT_RUMBLE_INTENSITY: u32 = 2;
 
 struct ButtonState {
     pressed_at: Option<Instant>,
@@ -32,20 +38,49 @@ pub async fn input(button_sender: mpsc::Sender<SystemMessage>) {
         cancel_rumble: None,
     };
 
-    let (mut button_recv, task) = input_task().await.unwrap();
+    let (button_recv, task) = input_task().await.unwrap();
+    let (mut button_recv, repeat_task) = key_repeat(button_recv, Repeat::default());
 
     tokio::spawn(task);
+    tokio::spawn(repeat_task);
+
+    let mut select_held = false;
+    // Select is held back from the ui until released, and dropped if it was used for brightness
+    let mut select_used = false;
 
     // Wait a bit before starting to take input
     tokio::time::sleep(Duration::from_millis(250)).await;
     while let Some(event) = button_recv.recv().await {
         match event.button() {
+            Button::Select if event.pressed() => {
+                select_held = true;
+                select_used = false;
+            }
+            Button::Select if event.released() => {
+                select_held = false;
+                if !select_used && !sleeping() && !emulator::playing() {
+                    let press = ButtonEvent::new(Button::Select, EventValue::Pressed);
+                    for event in [press, event] {
+                        button_sender
+                            .send(SystemMessage::ButtonEvent(event))
+                            .await
+                            .unwrap();
+                    }
+                }
+            }
+            // Repeats while held are only useful for navigation
+            Button::Select => {}
             // Handle power presses here, they should not be sent to ui
             Button::Power => handle_power(event, &button_sender, &mut power_state).await,
             // Handle menu presses here, only if not sleeping
             Button::Menu if !sleeping() => {
                 handle_menu(event, &button_sender, &mut menu_state).await
             }
+            // Volume keys work everywhere, even in game
+            Button::VolUp | Button::VolDown if !sleeping() => {
+                select_used |= select_held;
+                handle_volume(event, select_held, &button_sender).await
+            }
             // Only send button events to ui if not asleep and not playing game
             _ if !sleeping() && !emulator::playing() => button_sender
                 .send(SystemMessage::ButtonEvent(event))
@@ -53,11 +88,87 @@ pub async fn input(button_sender: mpsc::Sender<SystemMessage>) {
                 .unwrap(),
             _ => {}
         }
+
+        // After handling, so waking from sleep counts as activity
+        idle::activity();
     }
 
     panic!("Main input task ended!");
 }
 
+/// Rumbles for `duration` scaled by the rumble intensity setting
+async fn rumble(duration: Duration) -> io::Result<()> {
+    let intensity = settings::current().rumble_intensity as u32;
+    if intensity == 0 {
+        return Ok(());
+    }
+
+    // The motor is either on or off, so intensity is how long it stays on
+    hal()
+        .rumble(duration * intensity / DEFAULT_RUMBLE_INTENSITY)
+        .await
+}
+
+/// Changes volume, or brightness while Select is held, and shows the new level
+async fn handle_volume(
+    event: ButtonEvent,
+    brightness: bool,
+    button_sender: &mpsc::Sender<SystemMessage>,
+) {
+    if !event.triggered() {
+        return;
+    }
+
+    let up = *event.button() == Button::VolUp;
+    let step = |value: u8, max: u8| {
+        if up {
+            (value + 1).min(max)
+        } else {
+            value.saturating_sub(1)
+        }
+    };
+
+    let settings = settings::update(|settings| {
+        if brightness {
+            settings.brightness = step(settings.brightness, settings::MAX_BRIGHTNESS);
+        } else {
+            settings.volume = step(settings.volume, settings::MAX_VOLUME);
+        }
+    });
+
+    let overlay = if brightness {
+        Overlay {
+            kind: OverlayKind::Brightness,
+            level: settings.brightness,
+            max: settings::MAX_BRIGHTNESS,
+        }
+    } else {
+        Overlay {
+            kind: OverlayKind::Volume,
+            level: settings.volume,
+            max: settings::MAX_VOLUME,
+        }
+    };
+
+    if emulator::playing() {
+        // The ui is hidden behind the emulator, so it has to draw the overlay
+        tokio::spawn(async move {
+            if let Err(err) = ipc::client::call::<ShowOverlay>(ShowOverlayArgs { overlay }).await {
+                tracing::error!("Failed to show overlay in emulator: {err:?}");
+            }
+        });
+    }
+
+    button_sender
+        .send(SystemMessage::SettingsChanged)
+        .await
+        .unwrap();
+    button_sender
+        .send(SystemMessage::Overlay(overlay))
+        .await
+        .unwrap();
+}
+
 async fn handle_menu(
     event: ButtonEvent,
     button_sender: &mpsc::Sender<SystemMessage>,
@@ -70,7 +181,7 @@ async fn handle_menu(
         tokio::spawn(async move {
             select!(
                 _ = tokio::time::sleep(Duration::from_millis(MAIN_MENU_TIME)) => {
-                    if let Err(err) = rumble_for_sync(Duration::from_millis(50)).await {
+                    if let Err(err) = rumble(Duration::from_millis(50)).await {
                         tracing::error!("Failed to menu rumble: {err:?}");
                     }
                 },
@@ -125,7 +236,7 @@ async fn handle_power(
             select!(
                 _ = tokio::time::sleep(Duration::from_millis(POWER_OFF_TIME)) => {
                     // 3 secs passed since power press, rumble it
-                    if let Err(err) = rumble_for_sync(Duration::from_millis(50)).await {
+                    if let Err(err) = rumble(Duration::from_millis(50)).await {
                         tracing::error!("Failed to power rumble: {err:?}");
                     }
                 },
@@ -143,35 +254,22 @@ async fn handle_power(
 
             if held_for >= Duration::from_millis(POWER_OFF_TIME) {
                 // Held for long enough, shutdown
-
-                tracing::debug!("Shutting down...");
-                button_sender.send(SystemMessage::Shutdown).await.unwrap();
-
-                // If playing. make sure emulator saved
-                if emulator::playing() {
-                    match emulator::stop_playing().await {
-                        Ok(_) => {}
-                        Err(err) => tracing::error!("Failed to save: {err:?}"),
-                    };
-                } else {
-                    tokio::time::sleep(Duration::from_millis(500)).await;
-                }
-
-                nix::unistd::sync();
-                tokio::time::sleep(Duration::from_millis(300)).await;
-                std::process::Command::new("poweroff").output().unwrap();
-                loop {}
+                power_off(button_sender).await;
             } else {
                 // Shorter, sleep/wake
                 if sleeping() {
                     tracing::debug!("Waking...");
-                    wake().await.ok();
+                    if let Err(err) = wake().await {
+                        tracing::error!("Failed to wake: {err:?}");
+                    }
                     button_sender.send(SystemMessage::Wake).await.unwrap();
                 } else {
                     println!("sleeping");
                     tracing::debug!("Sleeping...");
-                    sleep().await.ok();
-                    button_sender.send(SystemMessage::Sleep).await.unwrap();
+                    match sleep().await {
+                        Ok(_) => button_sender.send(SystemMessage::Sleep).await.unwrap(),
+                        Err(err) => tracing::error!("Failed to sleep: {err:?}"),
+                    }
                 }
             }
 
@@ -184,3 +282,29 @@ async fn handle_power(
         }
     }
 }
+
+/// Saves the running game and anything not yet written, then powers off
+pub(crate) async fn power_off(button_sender: &mpsc::Sender<SystemMessage>) -> ! {
+    tracing::debug!("Shutting down...");
+    button_sender.send(SystemMessage::Shutdown).await.unwrap();
+
+    // If playing. make sure emulator saved
+    if emulator::playing() {
+        match emulator::stop_for_power_off().await {
+            Ok(_) => {}
+            Err(err) => tracing::error!("Failed to save: {err:?}"),
+        };
+    } else {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    // Don't leave writing play time up to the task, it may not get to it
+    play_time::flush().await;
+
+    nix::unistd::sync();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    if let Err(err) = hal().power_off().await {
+        tracing::error!("Failed to power off: {err:?}");
+    }
+    loop {}
+}
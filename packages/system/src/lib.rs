@@ -1,7 +1,12 @@
 mod battery;
 pub mod emulator;
+pub mod favorites;
 pub mod games;
+pub mod history;
+mod idle;
 mod input_task;
+pub mod play_time;
+mod server;
 pub mod settings;
 pub mod sleep;
 
@@ -9,16 +14,18 @@ use std::future::Future;
 
 use ::input::ButtonEvent;
 use battery::battery;
+use favorites::Favorites;
 use futures_util::future::join;
-use games::GameCache;
+use games::{bios::BiosCheck, Game, GameCache};
 use input_task::input;
 use launch::launch;
-use miyoo_mini_hal::model::{model, Model};
+use miyoo_mini_hal::{hal, model::Model, Backend};
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use sysinfo::System;
 use tokio::sync::mpsc;
 
+pub use ipc::functions::{Event as EmulatorEvent, Overlay, OverlayKind};
 pub use settings::Settings;
 
 static SYSTEM: Lazy<Mutex<System>> = Lazy::new(|| Mutex::new(System::new_all()));
@@ -27,19 +34,34 @@ static SYSTEM: Lazy<Mutex<System>> = Lazy::new(|| Mutex::new(System::new_all()))
 pub enum SystemMessage {
     ButtonEvent(ButtonEvent),
     BatteryPercentage(u8),
+    /// Sent whenever the charger is plugged in or out
+    Charging(bool),
+    /// Battery dropped below a warning threshold, holds the percentage
+    LowBattery(u8),
     Error(String),
     Sleep,
     Wake,
     Shutdown,
     MainMenu,
     Switcher,
+    /// Settings were changed outside of the ui, like with the volume keys.
+    /// Get them from `settings::current`
+    SettingsChanged,
+    /// Briefly show a level bar
+    Overlay(Overlay),
+    /// Sent by the emulator, see `ipc::functions::Event`
+    Emulator(EmulatorEvent),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Init {
     pub model: Model,
     pub settings: Settings,
+    pub favorites: Favorites,
     pub games: GameCache,
+    /// Game relaunched on boot, the ui should go straight to playing it
+    pub resumed: Option<Game>,
+    pub bios: Vec<BiosCheck>,
 }
 
 /// Starts a background task that handles system stuff like battery percentage, input, etc
@@ -50,9 +72,11 @@ pub fn task() -> (
     let (event_sender, event_receiver) = mpsc::channel(64);
 
     (event_receiver, async move {
-        let (settings, games) = join(
+        let ((settings, favorites, resumed), games) = join(
             async move {
                 launch().await.unwrap();
+                // Before anything is played, so cores find their files
+                games::bios::migrate().await;
 
                 tokio::spawn(input(event_sender.clone()));
 
@@ -60,19 +84,44 @@ pub fn task() -> (
 
                 let (settings, settings_recv) = Settings::init().await.unwrap();
                 tokio::spawn(settings::task(settings_recv));
+                tokio::spawn(idle::task(event_sender.clone()));
 
-                tokio::spawn(emulator::task(event_sender.clone()));
+                let (favorites, favorites_recv) = Favorites::init().await.unwrap();
+                tokio::spawn(favorites::task(favorites_recv));
 
-                settings
+                let history_recv = history::init().await.unwrap();
+                tokio::spawn(history::task(history_recv));
+
+                let play_time_recv = play_time::init().await.unwrap();
+                tokio::spawn(play_time::task(play_time_recv));
+
+                tokio::spawn(server::task(event_sender.clone()));
+
+                let proc_recv = emulator::init();
+                tokio::spawn(emulator::task(event_sender.clone(), proc_recv));
+
+                let resumed = match settings.resume_on_boot {
+                    true => emulator::resume_game().await,
+                    false => None,
+                };
+                if let Some(game) = resumed.as_ref() {
+                    tracing::debug!("Resuming {}", game.as_path().display());
+                    emulator::play(game);
+                }
+
+                (settings, favorites, resumed)
             },
             games::init(),
         )
         .await;
 
         Init {
-            model: model().await.unwrap(),
+            model: hal().model().await.unwrap(),
             settings,
+            favorites,
             games: games.unwrap(),
+            resumed,
+            bios: games::bios::check().await,
         }
     })
 }
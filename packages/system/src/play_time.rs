@@ -0,0 +1,304 @@
+use std::{
+    io,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant, SystemTime},
+};
+
+use layout::layout;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::{select, sync::mpsc};
+
+use crate::games::{Console, Game};
+
+static PLAY_TIME: Lazy<Mutex<PlayTime>> = Lazy::new(|| Mutex::new(PlayTime::default()));
+static PLAY_TIME_SENDER: OnceCell<mpsc::Sender<PlayTime>> = OnceCell::new();
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(0);
+
+const PLAY_TIME_FILE: &str = "play_time.json";
+/// How often a running session is written out, so at most this much is lost on power loss
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PlayTime {
+    games: Vec<GameStats>,
+    /// Session that was running when this was saved, it is closed on the next boot
+    /// if the device lost power or the os was killed before it could be stopped
+    session: Option<Session>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GameStats {
+    pub game: Game,
+    /// Total seconds played
+    pub play_time: u64,
+    pub sessions: u32,
+    /// Unix timestamp in seconds
+    pub last_played: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsoleStats {
+    pub console: Console,
+    /// Total seconds played
+    pub play_time: u64,
+    pub sessions: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct Session {
+    #[serde(skip)]
+    id: u64,
+    game: Game,
+    /// Time played up to the last pause or checkpoint
+    played: Duration,
+    /// `None` while paused
+    #[serde(skip)]
+    resumed_at: Option<Instant>,
+}
+
+impl Session {
+    fn elapsed(&self) -> Duration {
+        self.played
+            + self
+                .resumed_at
+                .map_or(Duration::ZERO, |resumed_at| resumed_at.elapsed())
+    }
+}
+
+impl PlayTime {
+    /// Adds a finished session to the game's totals
+    fn close(&mut self, game: &Game, played: Duration) {
+        let now = unix_now();
+
+        match self
+            .games
+            .iter_mut()
+            .find(|stats| stats.game.as_path() == game.as_path())
+        {
+            Some(stats) => {
+                stats.game = game.clone();
+                stats.play_time += played.as_secs();
+                stats.sessions += 1;
+                stats.last_played = now;
+            }
+            None => self.games.push(GameStats {
+                game: game.clone(),
+                play_time: played.as_secs(),
+                sessions: 1,
+                last_played: now,
+            }),
+        }
+    }
+
+    /// Copy with the running session's time folded in, this is what gets written to disk
+    fn snapshot(&self) -> Self {
+        let mut snapshot = self.clone();
+        if let Some(session) = snapshot.session.as_mut() {
+            session.played = session.elapsed();
+        }
+        snapshot
+    }
+}
+
+/// Loads play time from disk and closes a session left over from a power loss
+pub(crate) async fn init() -> io::Result<mpsc::Receiver<PlayTime>> {
+    let mut play_time = match persist::read_with(layout().data(PLAY_TIME_FILE), |bytes| {
+        serde_json::from_slice::<PlayTime>(&bytes)
+    })
+    .await
+    {
+        Ok(play_time) => play_time,
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            tracing::error!("Found invalid play time file. Starting empty. {err:?}");
+            PlayTime::default()
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => PlayTime::default(),
+        Err(err) => return Err(err),
+    };
+
+    if let Some(session) = play_time.session.take() {
+        tracing::warn!(
+            "Closing unfinished session for {}",
+            session.game.as_path().display()
+        );
+        play_time.close(&session.game, session.played);
+        write(&play_time).await;
+    }
+
+    tracing::debug!("Found play time for {} games", play_time.games.len());
+    *PLAY_TIME.lock() = play_time;
+
+    let (send, recv) = mpsc::channel(16);
+    PLAY_TIME_SENDER.set(send).ok();
+
+    Ok(recv)
+}
+
+/// Play time of every game that has been played, most played first
+pub fn games() -> Vec<GameStats> {
+    let mut games = PLAY_TIME.lock().games.clone();
+    games.sort_by(|a, b| b.play_time.cmp(&a.play_time));
+    games
+}
+
+/// Play time of a single game, if it has been played
+pub fn game(game: &Game) -> Option<GameStats> {
+    PLAY_TIME
+        .lock()
+        .games
+        .iter()
+        .find(|stats| stats.game.as_path() == game.as_path())
+        .cloned()
+}
+
+/// Totals for each console that has been played
+pub fn consoles() -> Vec<ConsoleStats> {
+    let play_time = PLAY_TIME.lock();
+
+    Console::iter()
+        .filter_map(|console| {
+            let mut stats = ConsoleStats {
+                console,
+                play_time: 0,
+                sessions: 0,
+            };
+            for game in play_time
+                .games
+                .iter()
+                .filter(|stats| *stats.game.console() == console)
+            {
+                stats.play_time += game.play_time;
+                stats.sessions += game.sessions;
+            }
+            (stats.sessions > 0).then_some(stats)
+        })
+        .collect()
+}
+
+/// Starts a new session for `game`, returns its id for `stop`
+pub(crate) fn start(game: &Game) -> u64 {
+    let mut play_time = PLAY_TIME.lock();
+
+    if let Some(session) = play_time.session.take() {
+        tracing::warn!("Started a session while another was running");
+        play_time.close(&session.game, session.elapsed());
+    }
+
+    let id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+    play_time.session = Some(Session {
+        id,
+        game: game.clone(),
+        played: Duration::ZERO,
+        resumed_at: Some(Instant::now()),
+    });
+    save(&play_time);
+
+    id
+}
+
+/// Id of the running session, if any
+pub(crate) fn current() -> Option<u64> {
+    PLAY_TIME.lock().session.as_ref().map(|session| session.id)
+}
+
+/// Stops counting time for the running session, used while the emulator is stopped for sleep
+pub(crate) fn pause() {
+    let mut play_time = PLAY_TIME.lock();
+
+    if let Some(session) = play_time.session.as_mut() {
+        session.played = session.elapsed();
+        session.resumed_at = None;
+    }
+    save(&play_time);
+}
+
+/// Picks the running session back up after `pause`
+pub(crate) fn resume() {
+    let mut play_time = PLAY_TIME.lock();
+
+    if let Some(session) = play_time.session.as_mut() {
+        if session.resumed_at.is_none() {
+            session.resumed_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Closes session `id` if it is still running, returning the game and how long it was played
+pub(crate) fn stop(id: u64) -> Option<(Game, Duration)> {
+    let mut play_time = PLAY_TIME.lock();
+
+    if play_time.session.as_ref()?.id != id {
+        return None;
+    }
+
+    let session = play_time.session.take()?;
+    let played = session.elapsed();
+    play_time.close(&session.game, played);
+    save(&play_time);
+
+    Some((session.game, played))
+}
+
+/// Writes play time right away, for when the device is about to power off
+pub(crate) async fn flush() {
+    let snapshot = PLAY_TIME.lock().snapshot();
+    write(&snapshot).await;
+}
+
+fn save(play_time: &PlayTime) {
+    match PLAY_TIME_SENDER.get() {
+        Some(sender) => {
+            if let Err(err) = sender.try_send(play_time.snapshot()) {
+                tracing::error!("Failed to queue play time save: {err:?}");
+            }
+        }
+        None => tracing::error!("Play time saved before init!"),
+    }
+}
+
+async fn write(play_time: &PlayTime) {
+    if let Err(err) = persist::write(
+        layout().data(PLAY_TIME_FILE),
+        serde_json::to_vec_pretty(play_time).unwrap(),
+    )
+    .await
+    {
+        tracing::error!("Failed to save play time: {err:?}")
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Persist play time updates, checkpointing running sessions every so often
+pub async fn task(mut recv: mpsc::Receiver<PlayTime>) {
+    let mut checkpoint = tokio::time::interval(CHECKPOINT_INTERVAL);
+
+    loop {
+        select! {
+            play_time = recv.recv() => match play_time {
+                Some(play_time) => write(&play_time).await,
+                None => break,
+            },
+            _ = checkpoint.tick() => {
+                let snapshot = {
+                    let play_time = PLAY_TIME.lock();
+                    match &play_time.session {
+                        Some(session) if session.resumed_at.is_some() => Some(play_time.snapshot()),
+                        _ => None,
+                    }
+                };
+
+                if let Some(snapshot) = snapshot {
+                    write(&snapshot).await;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Ipc served to the emulator, on its own socket since the emulator serves the other one
+
+use ipc::{
+    functions::{Event, Log, LogArgs, SendEvent, SendEventArgs},
+    Router, RouterExt,
+};
+use tokio::sync::mpsc;
+
+use crate::{emulator, SystemMessage};
+
+pub(crate) async fn task(event_sender: mpsc::Sender<SystemMessage>) {
+    let socket = ipc::system_socket_path();
+    if let Some(dir) = socket.parent() {
+        tokio::fs::create_dir_all(dir).await.ok();
+    }
+    // Left behind if the os didn't exit cleanly
+    tokio::fs::remove_file(&socket).await.ok();
+
+    let router = Router::new()
+        .function(Log, |LogArgs { lines }| async move {
+            logging::forwarded(&lines);
+            Ok(())
+        })
+        .function(SendEvent, |SendEventArgs { event }| async move {
+            // Handling can call back into the emulator, so don't hold up its request
+            tokio::spawn(handle(event, event_sender));
+            Ok(())
+        });
+
+    if let Err(err) = ipc::server::server_at(socket, router).await {
+        tracing::error!("System ipc server stopped: {err:?}");
+    }
+}
+
+/// Acts on an event from the emulator and passes it on to the ui
+async fn handle(event: Event, event_sender: mpsc::Sender<SystemMessage>) {
+    tracing::debug!("Emulator event: {event:?}");
+    match event {
+        Event::ShutdownRequested => {
+            if let Err(err) = emulator::stop_playing().await {
+                event_sender.send(SystemMessage::Error(err)).await.unwrap();
+                // Only still running if it couldn't save state
+                if emulator::playing() {
+                    return;
+                }
+            }
+            event_sender.send(SystemMessage::MainMenu).await.unwrap();
+        }
+        event => event_sender
+            .send(SystemMessage::Emulator(event))
+            .await
+            .unwrap(),
+    }
+}
@@ -1,115 +1,362 @@
-use std::io;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
-use once_cell::sync::OnceCell;
+use layout::layout;
+use miyoo_mini_hal::{hal, screen, sound, Backend};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use tokio::{
-    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
-    sync::mpsc,
-};
+use tokio::sync::watch;
+
+/// Only holds the latest settings, so updates made faster than they can be saved are coalesced
+pub(crate) static SETTINGS_SENDER: OnceCell<watch::Sender<Settings>> = OnceCell::new();
+/// Latest settings, for system tasks that don't get them from the ui
+static SETTINGS: Lazy<Mutex<Settings>> = Lazy::new(|| Mutex::new(Settings::default()));
 
-pub(crate) static SETTINGS_SENDER: OnceCell<mpsc::Sender<Settings>> = OnceCell::new();
+const SETTINGS_FILE: &str = "settings.json";
+/// Bump this and add a step to `migrate` whenever old settings files need changing
+pub const SETTINGS_VERSION: u32 = 2;
+
+pub const MAX_BRIGHTNESS: u8 = screen::MAX_BRIGHTNESS;
+pub const MAX_VOLUME: u8 = sound::MAX_VOLUME as u8;
+pub const MAX_RUMBLE_INTENSITY: u8 = 4;
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Settings {
+    pub version: u32,
+    /// 0 to `MAX_BRIGHTNESS`
     pub brightness: u8,
+    /// 0 to `MAX_VOLUME`
     pub volume: u8,
+    /// Idle timeouts in the menus
+    pub idle: IdlePolicy,
+    /// Idle timeouts while a game is running, these should be longer
+    pub in_game_idle: IdlePolicy,
+    /// Seconds asleep before deep sleeping, which saves and powers off, 0 to never
+    pub power_off_timeout: u32,
+    /// Percentages to warn at as the battery drains
+    pub battery_warnings: Vec<u8>,
+    /// Percentage to save and power off at, so games aren't lost to a dead battery
+    pub critical_battery: u8,
+    /// Relaunch the game that was running when the device powered off
+    pub resume_on_boot: bool,
+    /// 0 to `MAX_RUMBLE_INTENSITY`, 0 turns rumble off
+    pub rumble_intensity: u8,
+    pub theme: Theme,
+    pub clock_format: ClockFormat,
+    /// Log filter for the os and emulator, like `info` or `debug,winit=warn`
+    pub log_filter: String,
+    /// Emulator logs go to the os log instead of their own file
+    pub forward_emulator_logs: bool,
 }
 
-impl Settings {
-    pub async fn init() -> io::Result<(Self, mpsc::Receiver<Self>)> {
-        let mut settings_string = String::with_capacity(16 * 16);
-        let mut file = tokio::fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open("settings.json")
-            .await?;
-        let bytes_read = file.read_to_string(&mut settings_string).await?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IdlePolicy {
+    /// Seconds without input before dimming the backlight, 0 to never dim
+    pub dim_after: u32,
+    /// Seconds after dimming (or the last input if dimming is off) before going to sleep, 0 to never sleep
+    pub sleep_after: u32,
+}
 
-        let settings = match serde_json::from_str::<Settings>(&settings_string) {
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ClockFormat {
+    #[default]
+    TwentyFourHour,
+    TwelveHour,
+}
+
+impl Settings {
+    pub async fn init() -> io::Result<(Self, watch::Receiver<Self>)> {
+        let mut settings = match persist::read_with(layout().data(SETTINGS_FILE), parse).await {
             Ok(settings) => {
                 // Found valid settings file
                 tracing::debug!("Found valid settings: {settings:?}");
                 settings
             }
-            Err(_) if bytes_read == 0 => {
-                // No / empty settings file
-                tracing::debug!("No / empty settings.");
-                let settings = Settings::default();
-                file.write_all(&serde_json::to_vec_pretty(&settings).unwrap())
-                    .await?;
-                settings
-            }
-            Err(err) => {
-                // Invalid settings file
-                let settings = Settings::default();
-                file.write_all(&serde_json::to_vec_pretty(&settings).unwrap())
-                    .await?;
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                // Invalid settings file and backup
                 tracing::error!("Found invalid settings file. Using default. {err:?}");
-                settings
+                Settings::default()
             }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                tracing::debug!("No settings file.");
+                Settings::default()
+            }
+            Err(err) => return Err(err),
         };
 
-        let (send, recv) = mpsc::channel(16);
+        if settings.version > SETTINGS_VERSION {
+            match back_up_newer(&layout().data(SETTINGS_FILE), settings.version).await {
+                Ok(backup) => tracing::warn!(
+                    "Settings are from a newer version {}, backed up to {}",
+                    settings.version,
+                    backup.display()
+                ),
+                Err(err) => tracing::error!("Failed to back up newer settings: {err:?}"),
+            }
+            settings.version = SETTINGS_VERSION;
+        } else {
+            // Write back so defaulted or migrated settings are saved in the current format
+            persist::write(
+                layout().data(SETTINGS_FILE),
+                serde_json::to_vec_pretty(&settings).unwrap(),
+            )
+            .await?;
+        }
+
+        apply(&settings, None).await;
+        *SETTINGS.lock() = settings.clone();
+
+        let (send, recv) = watch::channel(settings.clone());
         SETTINGS_SENDER.set(send).ok();
 
         Ok((settings, recv))
     }
-
-    /// Use this to update settings as it will inform the respective system task
-    pub fn update(&mut self, op: impl FnOnce(&mut Self)) {
-        op(self);
-        SETTINGS_SENDER
-            .get()
-            .unwrap()
-            .try_send(self.clone())
-            .unwrap();
-    }
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             brightness: 6,
             volume: 8,
+            idle: IdlePolicy {
+                dim_after: 60,
+                sleep_after: 4 * 60,
+            },
+            in_game_idle: IdlePolicy {
+                dim_after: 5 * 60,
+                sleep_after: 10 * 60,
+            },
+            power_off_timeout: 60 * 60,
+            battery_warnings: vec![20, 10, 5],
+            critical_battery: 3,
+            resume_on_boot: true,
+            rumble_intensity: 2,
+            theme: Theme::default(),
+            clock_format: ClockFormat::default(),
+            log_filter: logging::DEFAULT_FILTER.into(),
+            forward_emulator_logs: false,
         }
     }
 }
 
-/// Handle settings updates from the ui
-pub async fn task(mut recv: mpsc::Receiver<Settings>) {
-    let mut prev_brightness: Option<u8> = None;
-    let mut prev_volume: Option<u8> = None;
-    let mut file = tokio::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(false)
-        .open("settings.json")
-        .await
-        .unwrap();
+/// Latest settings, the ui only keeps a copy of these for drawing
+pub fn current() -> Settings {
+    SETTINGS.lock().clone()
+}
 
-    loop {
-        while let Some(new_settings) = recv.recv().await {
-            if prev_brightness != Some(new_settings.brightness) {
-                // Brightness update
+/// The only way to change settings, so edits from the ui and system tasks can't overwrite
+/// each other. Returns the updated settings, anyone else showing them has to be told
+pub fn update(op: impl FnOnce(&mut Settings)) -> Settings {
+    let mut settings = SETTINGS.lock();
+    op(&mut settings);
+    send(settings.clone());
+    settings.clone()
+}
 
-                prev_brightness = Some(new_settings.brightness);
-            }
+fn send(settings: Settings) {
+    match SETTINGS_SENDER.get() {
+        Some(sender) => {
+            sender.send_replace(settings);
+        }
+        None => tracing::error!("Settings updated before init!"),
+    }
+}
 
-            if prev_volume != Some(new_settings.volume) {
-                // Volume update
+/// Parses a settings file of any version
+fn parse(bytes: Vec<u8>) -> serde_json::Result<Settings> {
+    serde_json::from_slice::<serde_json::Value>(&bytes)
+        .and_then(|value| serde_json::from_value::<Settings>(migrate(value)))
+}
 
-                prev_volume = Some(new_settings.volume)
-            }
+/// Copies a settings file written by a newer os next to it, since saving drops whatever
+/// this version doesn't know about
+async fn back_up_newer(file: &Path, version: u32) -> io::Result<PathBuf> {
+    let backup = file.with_file_name(format!("settings.v{version}.json"));
+    tokio::fs::copy(file, &backup).await?;
+    Ok(backup)
+}
 
-            // Save to file
-            file.seek(io::SeekFrom::Start(0)).await.unwrap();
-            if let Err(err) = file
-                .write_all(&serde_json::to_vec_pretty(&new_settings).unwrap())
-                .await
-            {
-                tracing::error!("Failed to save settings: {err:?}")
+/// Upgrades a settings file from any older version, one version at a time.
+/// Fields that don't exist yet are filled in with defaults when deserializing
+fn migrate(mut value: serde_json::Value) -> serde_json::Value {
+    // Files from before versioning have no version field
+    let version = value
+        .get("version")
+        .and_then(|version| version.as_u64())
+        .unwrap_or(0) as u32;
+
+    for from in version..SETTINGS_VERSION {
+        tracing::debug!("Migrating settings from version {from}");
+        match from {
+            // Only brightness and volume existed, both keep the same range
+            0 => {}
+            // A single sleep timeout became the dim and sleep idle policy
+            1 => {
+                // Without a timeout, the new defaults are used
+                if let Some((object, sleep_timeout)) = value.as_object_mut().and_then(|object| {
+                    let sleep_timeout = object.remove("sleep_timeout")?.as_u64()?;
+                    Some((object, sleep_timeout))
+                }) {
+                    // Short timeouts skip dimming, so they still sleep after the same time
+                    let dim_after = if sleep_timeout > 60 { 60 } else { 0 };
+                    object.insert(
+                        "idle".into(),
+                        serde_json::json!({
+                            "dim_after": dim_after,
+                            "sleep_after": sleep_timeout - dim_after,
+                        }),
+                    );
+                }
             }
+            _ => unreachable!("No settings migration from version {from}"),
+        }
+    }
+
+    // Newer files keep their version so init can tell they weren't written by us
+    if version < SETTINGS_VERSION {
+        if let Some(object) = value.as_object_mut() {
+            object.insert("version".into(), SETTINGS_VERSION.into());
         }
     }
+
+    value
+}
+
+/// Applies settings to the hardware, only the ones that changed from `prev` if given
+async fn apply(settings: &Settings, prev: Option<&Settings>) {
+    if prev.map_or(true, |prev| prev.brightness != settings.brightness) {
+        if let Err(err) = hal().set_brightness(settings.brightness).await {
+            tracing::error!("Failed to set brightness: {err:?}");
+        }
+    }
+
+    if prev.map_or(true, |prev| prev.log_filter != settings.log_filter) {
+        logging::set_filter(&settings.log_filter);
+    }
+
+    if prev.map_or(true, |prev| prev.volume != settings.volume) {
+        if let Err(err) = hal().set_volume(settings.volume).await {
+            tracing::error!("Failed to set volume: {err:?}");
+        }
+    }
+}
+
+/// Applies and saves settings changed with `update`
+pub async fn task(mut recv: watch::Receiver<Settings>) {
+    let mut prev = current();
+
+    // Skips anything replaced while the last update was being saved, only the latest matters
+    while recv.changed().await.is_ok() {
+        let new_settings = recv.borrow_and_update().clone();
+        apply(&new_settings, Some(&prev)).await;
+
+        // Save to file
+        if let Err(err) = persist::write(
+            layout().data(SETTINGS_FILE),
+            serde_json::to_vec_pretty(&new_settings).unwrap(),
+        )
+        .await
+        {
+            tracing::error!("Failed to save settings: {err:?}")
+        }
+
+        prev = new_settings;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_v0() {
+        let settings = parse(br#"{"brightness": 3, "volume": 5}"#.to_vec()).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.brightness, 3);
+        assert_eq!(settings.volume, 5);
+        assert_eq!(settings.theme, Theme::default());
+        assert_eq!(settings.idle, Settings::default().idle);
+    }
+
+    #[test]
+    fn migrate_v1() {
+        let idle = |json: &[u8]| parse(json.to_vec()).unwrap().idle;
+
+        // Short timeouts skip dimming rather than never sleeping
+        assert_eq!(
+            idle(br#"{"version": 1, "sleep_timeout": 30}"#),
+            IdlePolicy {
+                dim_after: 0,
+                sleep_after: 30
+            }
+        );
+        assert_eq!(
+            idle(br#"{"version": 1, "sleep_timeout": 60}"#),
+            IdlePolicy {
+                dim_after: 0,
+                sleep_after: 60
+            }
+        );
+        assert_eq!(
+            idle(br#"{"version": 1, "sleep_timeout": 300}"#),
+            IdlePolicy {
+                dim_after: 60,
+                sleep_after: 240
+            }
+        );
+        assert_eq!(
+            idle(br#"{"version": 1, "sleep_timeout": 0}"#),
+            IdlePolicy {
+                dim_after: 0,
+                sleep_after: 0
+            }
+        );
+        assert_eq!(idle(br#"{"version": 1}"#), Settings::default().idle);
+
+        let settings = parse(br#"{"version": 1, "sleep_timeout": 300}"#.to_vec()).unwrap();
+        assert_eq!(settings.in_game_idle, Settings::default().in_game_idle);
+        let value = migrate(serde_json::json!({"version": 1, "sleep_timeout": 300}));
+        assert!(value.get("sleep_timeout").is_none());
+    }
+
+    #[test]
+    fn newer_version() {
+        let json = br#"{"version": 99, "brightness": 2, "added_later": true}"#;
+        let value = migrate(serde_json::from_slice(json).unwrap());
+        assert_eq!(value["version"], 99);
+        assert_eq!(value["added_later"], true);
+
+        let settings = parse(json.to_vec()).unwrap();
+        assert_eq!(settings.version, 99);
+        assert_eq!(settings.brightness, 2);
+    }
+
+    #[tokio::test]
+    async fn newer_version_backup() {
+        let dir = std::env::temp_dir().join(format!("settings-test-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let file = dir.join(SETTINGS_FILE);
+        tokio::fs::write(&file, r#"{"version": 99}"#).await.unwrap();
+
+        let backup = back_up_newer(&file, 99).await.unwrap();
+        assert_eq!(backup, dir.join("settings.v99.json"));
+        assert_eq!(
+            tokio::fs::read_to_string(&backup).await.unwrap(),
+            r#"{"version": 99}"#
+        );
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
 }
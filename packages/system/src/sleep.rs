@@ -5,22 +5,26 @@ use std::{
 };
 use sysinfo::{Pid, ProcessStatus, Signal};
 
-use miyoo_mini_hal::screen;
+use miyoo_mini_hal::{hal, Backend};
 
-use crate::{asyncify, emulator::playing, SYSTEM};
+use tokio::sync::mpsc;
+
+use crate::{asyncify, emulator::playing, input_task, play_time, SystemMessage, SYSTEM};
 
 static SUSPENDED: AtomicBool = AtomicBool::new(false);
 
 /// Preforms all needed operations to sleep the device (screen off etc.)
 pub async fn sleep() -> io::Result<()> {
     if playing() {
-        ipc::client::call::<ipc::functions::Stop>(StopArgs {})
-            .await
-            .unwrap();
+        ipc::client::call::<ipc::functions::Stop>(StopArgs {}).await?;
+        play_time::pause();
     }
 
-    stop_all_processes().await?;
-    screen::turn_off_screen().await?;
+    // Don't stop everything on a desktop
+    if !hal().is_simulated() {
+        stop_all_processes().await?;
+    }
+    hal().screen_off().await?;
     SUSPENDED.store(true, std::sync::atomic::Ordering::Relaxed);
     Ok(())
 }
@@ -29,15 +33,38 @@ pub async fn sleep() -> io::Result<()> {
 pub async fn wake() -> io::Result<()> {
     continue_all_processes().await?;
 
-    if playing() {
+    // The screen still has to come back if the emulator doesn't, so this is returned last
+    let started = if playing() {
         ipc::client::call::<ipc::functions::Start>(StartArgs {})
             .await
-            .unwrap();
-    }
+            .map(|_| play_time::resume())
+    } else {
+        Ok(())
+    };
 
-    screen::turn_on_screen().await?;
+    hal().screen_on().await?;
     SUSPENDED.store(false, std::sync::atomic::Ordering::Relaxed);
-    Ok(())
+    Ok(started?)
+}
+
+/// Saves everything and powers off after being asleep for too long, since the SoC keeps
+/// draining the battery while asleep. The next boot resumes the game from the auto save
+pub(crate) async fn deep_sleep(sender: &mpsc::Sender<SystemMessage>) -> ! {
+    tracing::debug!("Deep sleeping...");
+
+    // The emulator has to run again to save, the screen can stay off
+    if let Err(err) = continue_all_processes().await {
+        tracing::error!("Failed to continue processes for deep sleep: {err:?}");
+    }
+    if playing() {
+        if let Err(err) = ipc::client::call::<ipc::functions::Start>(StartArgs {}).await {
+            tracing::error!("Failed to start emulator for deep sleep: {err:?}");
+        }
+    }
+    SUSPENDED.store(false, std::sync::atomic::Ordering::Relaxed);
+
+    // Saves the auto slot and save ram, then syncs and powers off
+    input_task::power_off(sender).await
 }
 
 pub fn sleeping() -> bool {
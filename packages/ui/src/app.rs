@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, time::Duration};
 
 use iced::{
     executor, subscription,
@@ -8,22 +8,52 @@ use iced::{
 use iced_runtime::command::Action;
 use miyoo_mini_hal::model::Model;
 use once_cell::sync::Lazy;
-use system::{games::GameCache, Init, Settings, SystemMessage};
+use system::{
+    favorites::Favorites,
+    games::{bios::BiosCheck, GameCache},
+    settings, EmulatorEvent, Init, Settings, SystemMessage,
+};
 use tokio::sync::mpsc;
 
-use crate::{screens::Screen, Message};
+use crate::{
+    screens::{switcher, Screen},
+    thumbnail, Message,
+};
+
+/// How long the volume/brightness overlay stays up after the last change
+const OVERLAY_TIME: Duration = Duration::from_millis(1500);
+/// How long toasts like low battery warnings stay up
+const TOAST_TIME: Duration = Duration::from_secs(4);
 
 #[derive(Debug)]
 pub struct App {
     pub screen: Screen,
     pub battery_percentage: u8,
+    pub charging: bool,
     /// Defaults to MiniPlus but is updated after startup
     pub model: Model,
     pub settings: Settings,
+    pub favorites: Favorites,
     pub games: GameCache,
+    pub bios: Vec<BiosCheck>,
+    /// Level bar from the volume keys and its id, see `Message::HideOverlay`
+    pub overlay: Option<(Overlay, u64)>,
+    /// Short notice and its id, see `Message::HideToast`
+    pub toast: Option<(String, u64)>,
     pub event_receiver: RefCell<Option<mpsc::Receiver<SystemMessage>>>,
 }
 
+impl App {
+    /// Shows `text` for a few seconds
+    fn toast(&mut self, text: String) -> Command<Message> {
+        let id = self.toast.as_ref().map_or(0, |(_, id)| id + 1);
+        self.toast = Some((text, id));
+        Command::perform(tokio::time::sleep(TOAST_TIME), move |_| {
+            Message::HideToast(id)
+        })
+    }
+}
+
 impl Application for App {
     type Executor = executor::Default;
     type Flags = ();
@@ -36,9 +66,14 @@ impl Application for App {
             Self {
                 screen: Screen::default(),
                 battery_percentage: 100,
+                charging: false,
                 model: Model::MiniPlus,
                 settings: Settings::default(),
+                favorites: Favorites::default(),
                 games: GameCache::new(),
+                bios: vec![],
+                overlay: None,
+                toast: None,
                 event_receiver: RefCell::new(Some(event_receiver)),
             },
             // Start the system task and fullscreen the app
@@ -67,15 +102,19 @@ impl Application for App {
             Message::System(SystemMessage::Switcher) => {
                 if matches!(self.screen, Screen::Switcher) {
                     self.screen = Screen::Main;
+                    Command::none()
                 } else {
                     self.screen = Screen::Switcher;
+                    switcher::State::open()
                 }
-                Command::none()
             }
             Message::StartupDone(Init {
                 model,
                 settings,
+                favorites,
                 games,
+                resumed,
+                bios,
             }) => {
                 let command = Command::single(Action::Window(window::Action::Resize(
                     Id::MAIN,
@@ -86,14 +125,73 @@ impl Application for App {
                 )));
                 self.model = model;
                 self.settings = settings;
+                self.favorites = favorites;
                 self.games = games;
-                self.screen = Screen::Main;
-                command
+                let missing = bios
+                    .iter()
+                    .filter(|check| check.problem())
+                    .map(|check| check.file.name)
+                    .collect::<Vec<_>>();
+                self.bios = bios;
+                self.screen = match resumed {
+                    Some(_) => Screen::Playing(Some(Box::new(Screen::Main))),
+                    None => Screen::Main,
+                };
+
+                if missing.is_empty() {
+                    command
+                } else {
+                    Command::batch([
+                        command,
+                        self.toast(format!("Missing BIOS: {}", missing.join(", "))),
+                    ])
+                }
             }
             Message::System(SystemMessage::BatteryPercentage(percentage)) => {
                 self.battery_percentage = percentage;
                 Command::none()
             }
+            Message::System(SystemMessage::Charging(charging)) => {
+                self.charging = charging;
+                Command::none()
+            }
+            Message::System(SystemMessage::LowBattery(percentage)) => {
+                self.toast(format!("Battery low: {percentage}%"))
+            }
+            Message::System(SystemMessage::Emulator(EmulatorEvent::SaveFailed {
+                error, ..
+            })) => self.toast(format!("Save failed: {error}")),
+            Message::HideToast(id) => {
+                if self
+                    .toast
+                    .as_ref()
+                    .map_or(false, |(_, current)| *current == id)
+                {
+                    self.toast = None;
+                }
+                Command::none()
+            }
+            Message::System(SystemMessage::SettingsChanged) => {
+                self.settings = settings::current();
+                Command::none()
+            }
+            Message::System(SystemMessage::Overlay(overlay)) => {
+                let id = self.overlay.map_or(0, |(_, id)| id + 1);
+                self.overlay = Some((overlay, id));
+                Command::perform(tokio::time::sleep(OVERLAY_TIME), move |_| {
+                    Message::HideOverlay(id)
+                })
+            }
+            Message::HideOverlay(id) => {
+                if self.overlay.map_or(false, |(_, current)| current == id) {
+                    self.overlay = None;
+                }
+                Command::none()
+            }
+            Message::ThumbnailLoaded(path, handle) => {
+                thumbnail::insert(path, handle);
+                Command::none()
+            }
             _ => Screen::update(self, message),
         }
     }
@@ -115,6 +213,9 @@ impl Application for App {
     }
 
     fn theme(&self) -> Self::Theme {
-        Theme::Dark
+        match self.settings.theme {
+            settings::Theme::Dark => Theme::Dark,
+            settings::Theme::Light => Theme::Light,
+        }
     }
 }
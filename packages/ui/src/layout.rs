@@ -1,8 +1,11 @@
+use std::time::SystemTime;
+
 use iced::{
     color,
-    widget::{column, row, text},
+    widget::{column, progress_bar, row, text},
     Element, Length,
 };
+use system::{settings::ClockFormat, OverlayKind};
 
 use crate::{app::App, Message};
 
@@ -10,10 +13,63 @@ pub fn layout<'a>(app: &App, ui: Element<'a, Message>) -> Element<'a, Message> {
     column![
         row![
             text("Oxide").size(32).style(color!(0xF74C00)),
-            text(format!("{}%", app.battery_percentage))
-        ],
-        ui
+            text(clock(app.settings.clock_format)),
+            text(format!("{}%", app.battery_percentage)),
+            text(if app.charging { "CHG" } else { "" }).style(color!(0x4CAF50))
+        ]
+        .spacing(8),
+        ui,
+        toast(app),
+        overlay(app)
     ]
     .width(Length::Fill)
     .into()
 }
+
+/// Volume or brightness bar while it's being changed
+fn overlay<'a>(app: &App) -> Element<'a, Message> {
+    match app.overlay {
+        Some((overlay, _)) => row![
+            text(match overlay.kind {
+                OverlayKind::Volume => "Volume",
+                OverlayKind::Brightness => "Brightness",
+                OverlayKind::Battery => "Battery",
+            }),
+            progress_bar(0.0..=overlay.max as f32, overlay.level as f32).height(Length::Fixed(16.))
+        ]
+        .spacing(8)
+        .padding(8)
+        .align_items(iced::Alignment::Center)
+        .into(),
+        None => row![].into(),
+    }
+}
+
+/// Notices like low battery warnings
+fn toast<'a>(app: &App) -> Element<'a, Message> {
+    match app.toast.as_ref() {
+        Some((toast, _)) => row![text(toast).style(color!(0xFF2020))].padding(8).into(),
+        None => row![].into(),
+    }
+}
+
+/// Current time from the rtc, which is kept in local time so no timezone is applied
+fn clock(format: ClockFormat) -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs());
+    let hours = secs % (24 * 60 * 60) / (60 * 60);
+    let minutes = secs % (60 * 60) / 60;
+
+    match format {
+        ClockFormat::TwentyFourHour => format!("{hours:02}:{minutes:02}"),
+        ClockFormat::TwelveHour => {
+            let suffix = if hours < 12 { "AM" } else { "PM" };
+            let hours = match hours % 12 {
+                0 => 12,
+                hours => hours,
+            };
+            format!("{hours}:{minutes:02} {suffix}")
+        }
+    }
+}
@@ -2,6 +2,7 @@ mod app;
 mod layout;
 pub mod message;
 mod screens;
+mod thumbnail;
 
 use app::App;
 use iced::{window::Position, Application, Font, Pixels, Point, Settings, Size};
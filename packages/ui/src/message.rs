@@ -1,3 +1,6 @@
+use std::path::PathBuf;
+
+use iced::widget::image;
 use miyoo_mini_hal::model::Model;
 use system::{Init, SystemMessage};
 
@@ -5,4 +8,12 @@ use system::{Init, SystemMessage};
 pub enum Message {
     System(SystemMessage),
     StartupDone(Init),
+    /// Path of the game and its decoded thumbnail, if one was found
+    ThumbnailLoaded(PathBuf, Option<image::Handle>),
+    /// Hides the overlay if it hasn't been shown again since, holds the overlay's id
+    HideOverlay(u64),
+    /// Hides the toast if it hasn't been replaced since, holds the toast's id
+    HideToast(u64),
+    /// Index of the log on the logs screen and its last lines, or why it couldn't be read
+    LogRead(usize, Result<Vec<String>, String>),
 }
@@ -0,0 +1,119 @@
+use iced::{
+    color,
+    widget::{column, row, text},
+    Command, Element, Length,
+};
+use input::Button;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, MutexGuard};
+use shared_ui::{scrollable_list, ListItem, ScrollableList};
+use system::{
+    games::bios::{BiosCheck, BiosStatus},
+    SystemMessage,
+};
+
+use crate::{app::App, layout::layout, Message};
+
+use super::Screen;
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+#[derive(Debug)]
+pub struct State {
+    /// Checks the list was built from
+    checks: Vec<BiosCheck>,
+    list: ScrollableList<App>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            checks: vec![],
+            list: ScrollableList::new(vec![]),
+        }
+    }
+}
+
+impl State {
+    pub fn update(app: &mut App, message: Message) -> Command<Message> {
+        let mut state = STATE.lock();
+        state.sync(app);
+        match &message {
+            Message::System(SystemMessage::ButtonEvent(ev)) => match ev.button() {
+                Button::B if ev.pressed() => {
+                    app.screen = Screen::Main;
+                    Command::none()
+                }
+                Button::Up if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::Up)
+                }
+                Button::Down if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::Down)
+                }
+                _ => Command::none(),
+            },
+            _ => Command::none(),
+        }
+    }
+
+    pub fn view(app: &App) -> Element<Message> {
+        let mut state: MutexGuard<'static, State> = STATE.lock();
+        state.sync(app);
+
+        layout(
+            app,
+            column![
+                text("BIOS files go in the BIOS folder on the sd card.").size(16),
+                state.list.view(app)
+            ]
+            .spacing(8)
+            .padding(8)
+            .into(),
+        )
+    }
+
+    fn sync(&mut self, app: &App) {
+        if self.checks == app.bios {
+            return;
+        }
+
+        self.checks = app.bios.clone();
+        self.list = ScrollableList::new(
+            self.checks
+                .iter()
+                .map(|check| {
+                    let check = check.clone();
+
+                    ListItem::new(
+                        move |_app: &'_ App| {
+                            let (status, color) = match check.status {
+                                BiosStatus::Ok => ("Ok", color!(0x4CAF50)),
+                                BiosStatus::Missing if !check.file.required => {
+                                    ("Missing (optional)", color!(0x888888))
+                                }
+                                BiosStatus::Missing => ("Missing", color!(0xFF2020)),
+                                BiosStatus::WrongChecksum => ("Bad checksum", color!(0xFF2020)),
+                            };
+
+                            row![
+                                text(check.file.console.name())
+                                    .size(16)
+                                    .width(Length::Fixed(100.)),
+                                text(check.file.name).size(20).width(Length::Fill),
+                                text(status).size(16).style(color),
+                            ]
+                            .spacing(8)
+                            .align_items(iced::Alignment::Center)
+                            .into()
+                        },
+                        |_app: &'_ mut App, _message: Message| Command::none(),
+                    )
+                })
+                .collect(),
+        );
+    }
+}
@@ -8,8 +8,8 @@ use iced::{
 use input::Button;
 use once_cell::sync::Lazy;
 use parking_lot::{Mutex, MutexGuard};
-use shared_ui::{scrollable_list, ScrollableList};
-use system::SystemMessage;
+use shared_ui::{scrollable_list, ListItem, ScrollableList};
+use system::{emulator::play, games::Game, SystemMessage};
 
 use crate::{app::App, layout::layout, Message};
 
@@ -19,12 +19,15 @@ static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
 
 #[derive(Debug, Clone)]
 pub struct State {
+    /// Favorites the list was built from, used to rebuild when they change
+    games: Vec<Game>,
     list: ScrollableList<App>,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
+            games: vec![],
             list: ScrollableList::new(vec![]),
         }
     }
@@ -33,22 +36,33 @@ impl Default for State {
 impl State {
     pub fn update(app: &mut App, message: Message) -> Command<Message> {
         let mut state = STATE.lock();
+        state.sync(app);
         match &message {
             Message::System(SystemMessage::ButtonEvent(ev)) => match ev.button() {
                 Button::B if ev.pressed() => {
                     app.screen = Screen::Main;
                     Command::none()
                 }
-                Button::Up if ev.pressed() => {
+                Button::Up if ev.triggered() => {
                     state
                         .list
                         .update(app, message, scrollable_list::Message::Up)
                 }
-                Button::Down if ev.pressed() => {
+                Button::Down if ev.triggered() => {
                     state
                         .list
                         .update(app, message, scrollable_list::Message::Down)
                 }
+                Button::L1 if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::PageUp)
+                }
+                Button::R1 if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::PageDown)
+                }
                 _ => state
                     .list
                     .update(app, message, scrollable_list::Message::Other),
@@ -58,7 +72,91 @@ impl State {
     }
 
     pub fn view(app: &App) -> Element<Message> {
-        let state: MutexGuard<'static, State> = STATE.lock();
+        let mut state: MutexGuard<'static, State> = STATE.lock();
+        state.sync(app);
+
+        if state.games.is_empty() {
+            return layout(
+                app,
+                container(text(
+                    "No favorites yet. Press `Select` on a game to add it.",
+                ))
+                .center_x()
+                .center_y()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+            );
+        }
+
         layout(app, state.list.view(app))
     }
+
+    /// Rebuilds the list if favorites were added or removed since it was built
+    fn sync(&mut self, app: &App) {
+        if self.games.as_slice() == app.favorites.games() {
+            return;
+        }
+
+        let selected = self.list.selected();
+        self.games = app.favorites.games().to_vec();
+        self.list = ScrollableList::new(
+            self.games
+                .iter()
+                .map(|game| {
+                    let children_game = game.clone();
+                    let action_game = game.clone();
+
+                    ListItem::new(
+                        move |app: &'_ App| {
+                            let game = &children_game;
+                            let missing = app.favorites.is_missing(game);
+                            row![
+                                text(game.title()).size(20),
+                                text(game.console().name()).size(14).style(color!(0x888888)),
+                                text(if missing { "missing" } else { "" })
+                                    .size(14)
+                                    .style(color!(255, 0, 0))
+                            ]
+                            .spacing(8)
+                            .align_items(iced::Alignment::Center)
+                            .into()
+                        },
+                        move |app: &'_ mut App, message: Message| {
+                            let game = &action_game;
+
+                            match message {
+                                Message::System(SystemMessage::ButtonEvent(ev)) => {
+                                    match ev.button() {
+                                        Button::A if ev.pressed() => {
+                                            if app.favorites.is_missing(game) {
+                                                tracing::warn!(
+                                                    "Tried to play missing favorite: {}",
+                                                    game.as_path().display()
+                                                );
+                                                return Command::none();
+                                            }
+
+                                            play(game);
+                                            app.screen =
+                                                Screen::Playing(Some(Box::new(Screen::Favorites)));
+                                            Command::none()
+                                        }
+                                        Button::Select if ev.pressed() => {
+                                            // Unfavorite
+                                            app.favorites.remove(game);
+                                            Command::none()
+                                        }
+                                        _ => Command::none(),
+                                    }
+                                }
+                                _ => Command::none(),
+                            }
+                        },
+                    )
+                })
+                .collect(),
+        );
+        self.list.select(selected);
+    }
 }
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use iced::{
     border::Radius,
     color, theme,
-    widget::{column, container, row, scrollable, text, Text},
+    widget::{column, container, image, row, scrollable, text, Text},
     Background, Border, Command, Element, Length,
 };
 use input::Button;
@@ -12,13 +12,13 @@ use parking_lot::{Mutex, MutexGuard};
 use shared_ui::{scrollable_list, ListItem, ScrollableList};
 use system::{
     emulator::play,
-    games::{Console, Game},
+    games::{search::Filter, Console, Game},
     SystemMessage,
 };
 
-use crate::{app::App, layout::layout, Message};
+use crate::{app::App, layout::layout, thumbnail, Message};
 
-use super::Screen;
+use super::{search, Screen};
 
 static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
 
@@ -26,6 +26,8 @@ static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
 pub struct State {
     consoles: Vec<Console>,
     selected_console: Option<Console>,
+    /// Games of the selected console, in the same order as `game_list`
+    console_games: Arc<[Game]>,
     game_list: ScrollableList<App>,
     /// idx of selected button
     selected: usize,
@@ -37,6 +39,7 @@ impl Default for State {
         Self {
             consoles: Console::iter().collect(),
             game_list: ScrollableList::new(vec![]),
+            console_games: Arc::new([]),
             selected_console: None,
             selected: 0,
             page: 0,
@@ -57,15 +60,32 @@ impl State {
                             state.selected_console = None;
                             Command::none()
                         }
-                        Button::Up if ev.pressed() => {
-                            state
-                                .game_list
-                                .update(app, message, scrollable_list::Message::Up)
+                        Button::Y if ev.pressed() => {
+                            // Search within this console
+                            if let Some(console) = state.selected_console {
+                                search::State::open(Filter::Console(console));
+                                app.screen = Screen::Search;
+                            }
+                            Command::none()
                         }
-                        Button::Down if ev.pressed() => {
-                            state
-                                .game_list
-                                .update(app, message, scrollable_list::Message::Down)
+                        Button::Up
+                        | Button::Down
+                        | Button::L1
+                        | Button::R1
+                        | Button::L2
+                        | Button::R2
+                            if ev.triggered() =>
+                        {
+                            let mapped = match ev.button() {
+                                Button::Up => scrollable_list::Message::Up,
+                                Button::Down => scrollable_list::Message::Down,
+                                Button::L1 => scrollable_list::Message::PageUp,
+                                Button::R1 => scrollable_list::Message::PageDown,
+                                Button::L2 => scrollable_list::Message::PrevLetter,
+                                _ => scrollable_list::Message::NextLetter,
+                            };
+                            let command = state.game_list.update(app, message, mapped);
+                            Command::batch([command, state.load_selected_thumbnail()])
                         }
                         _ => state
                             .game_list
@@ -80,6 +100,7 @@ impl State {
                             state.selected_console = Some(selected);
                             let console_games: Arc<[Game]> =
                                 app.games.get(selected).cloned().unwrap();
+                            state.console_games = console_games.clone();
 
                             // Construct game list for selected console, giving each callback its own copy of games
                             state.game_list = ScrollableList::new(
@@ -89,11 +110,31 @@ impl State {
                                         // Make one clone for each callback
                                         let children_games = console_games.clone();
                                         let action_games = console_games.clone();
+                                        let letter = console_games[game_idx].metadata().letter();
 
                                         ListItem::new(
                                             move |app: &'_ App| {
                                                 let game = &children_games[game_idx];
-                                                text(game.full_name()).size(20).into()
+                                                row![
+                                                    text(game.title()).size(20),
+                                                    text(
+                                                        game.metadata()
+                                                            .region_badge()
+                                                            .unwrap_or_default()
+                                                    )
+                                                    .size(14)
+                                                    .style(color!(0x888888)),
+                                                    text(if app.favorites.contains(game) {
+                                                        "*"
+                                                    } else {
+                                                        ""
+                                                    })
+                                                    .size(20)
+                                                    .style(color!(0xF74C00))
+                                                ]
+                                                .spacing(8)
+                                                .align_items(iced::Alignment::Center)
+                                                .into()
                                             },
                                             move |app: &'_ mut App, message: Message| {
                                                 let game = &action_games[game_idx];
@@ -110,29 +151,34 @@ impl State {
 
                                                             Command::none()
                                                         }
+                                                        Button::Select if ev.pressed() => {
+                                                            app.favorites.toggle(game);
+                                                            Command::none()
+                                                        }
                                                         _ => Command::none(),
                                                     },
                                                     _ => Command::none(),
                                                 }
                                             },
                                         )
+                                        .with_letter(letter)
                                     })
                                     .collect(),
                             );
-                            Command::none()
+                            state.load_selected_thumbnail()
                         }
                         Button::B if ev.pressed() => {
                             app.screen = Screen::Main;
                             Command::none()
                         }
-                        Button::Right if ev.pressed() => {
+                        Button::Right if ev.triggered() => {
                             // Move to right if possible
                             if state.selected < state.consoles.len() - 1 {
                                 state.selected += 1;
                             };
                             Command::none()
                         }
-                        Button::Left if ev.pressed() => {
+                        Button::Left if ev.triggered() => {
                             // Move to left if possible
                             if state.selected > 0 {
                                 state.selected -= 1;
@@ -152,7 +198,13 @@ impl State {
         layout(
             app,
             if state.selected_console.is_some() {
-                state.game_list.view(app)
+                row![
+                    container(state.game_list.view(app)).width(Length::FillPortion(3)),
+                    state.thumbnail_view()
+                ]
+                .spacing(8)
+                .height(Length::Fill)
+                .into()
             } else {
                 row(state
                     .consoles
@@ -167,6 +219,34 @@ impl State {
             },
         )
     }
+
+    fn selected_game(&self) -> Option<&Game> {
+        self.console_games.get(self.game_list.selected())
+    }
+
+    fn load_selected_thumbnail(&self) -> Command<Message> {
+        match self.selected_game() {
+            Some(game) => thumbnail::load(game),
+            None => Command::none(),
+        }
+    }
+
+    fn thumbnail_view(&self) -> Element<'static, Message> {
+        let content: Element<'static, Message> = match self
+            .selected_game()
+            .and_then(|game| thumbnail::get(game.as_path()))
+        {
+            Some(handle) => image(handle).width(Length::Fill).into(),
+            None => text("No image").size(16).style(color!(0x888888)).into(),
+        };
+
+        container(content)
+            .center_x()
+            .center_y()
+            .width(Length::FillPortion(2))
+            .height(Length::Fill)
+            .into()
+    }
 }
 
 fn console_view(console: Console, selected: bool) -> Element<'static, Message> {
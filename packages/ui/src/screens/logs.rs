@@ -0,0 +1,184 @@
+use iced::{
+    color,
+    widget::{column, container, row, text},
+    Command, Element, Length,
+};
+use input::Button;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, MutexGuard};
+use shared_ui::{scrollable_list, ListItem, ScrollableList};
+use system::SystemMessage;
+
+use crate::{app::App, layout::layout, Message};
+
+use super::Screen;
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+/// Logs that can be shown, switched between with Select
+const LOGS: &[&str] = &["os", "emulator"];
+/// Lines read from the end of the log
+const MAX_LINES: usize = 200;
+
+#[derive(Debug)]
+pub struct State {
+    /// Index into `LOGS`
+    log: usize,
+    /// Read in the background when the screen is opened, `Err` if the log couldn't be read
+    lines: Option<Result<Vec<String>, String>>,
+    list: ScrollableList<App>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            log: 0,
+            lines: None,
+            list: ScrollableList::new(vec![]),
+        }
+    }
+}
+
+impl State {
+    /// Starts reading the log, call when switching to this screen
+    pub fn open() -> Command<Message> {
+        STATE.lock().read()
+    }
+
+    pub fn update(app: &mut App, message: Message) -> Command<Message> {
+        let mut state = STATE.lock();
+        match &message {
+            Message::LogRead(log, lines) => {
+                // Could be from before switching logs
+                if *log == state.log {
+                    state.set_lines(lines.clone());
+                }
+                Command::none()
+            }
+            Message::System(SystemMessage::ButtonEvent(ev)) => match ev.button() {
+                Button::B if ev.pressed() => {
+                    // Read again next time
+                    state.lines = None;
+                    app.screen = Screen::Main;
+                    Command::none()
+                }
+                Button::Select if ev.pressed() => {
+                    state.log = (state.log + 1) % LOGS.len();
+                    state.read()
+                }
+                Button::X if ev.pressed() => state.read(),
+                Button::Up if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::Up)
+                }
+                Button::Down if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::Down)
+                }
+                Button::L1 if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::PageUp)
+                }
+                Button::R1 if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::PageDown)
+                }
+                _ => Command::none(),
+            },
+            _ => Command::none(),
+        }
+    }
+
+    pub fn view(app: &App) -> Element<Message> {
+        let state: MutexGuard<'static, State> = STATE.lock();
+
+        let header = row![
+            text(format!("{}.log", LOGS[state.log]))
+                .size(20)
+                .width(Length::Fill),
+            text("Select: switch  X: reload")
+                .size(14)
+                .style(color!(0x888888)),
+        ]
+        .align_items(iced::Alignment::Center);
+
+        let body: Element<Message> = match state.lines.as_ref() {
+            Some(Ok(lines)) if !lines.is_empty() => state.list.view(app),
+            Some(Err(err)) => container(text(err.clone()).size(16))
+                .center_x()
+                .center_y()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+            None => container(text("Reading log..."))
+                .center_x()
+                .center_y()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+            _ => container(text("Nothing logged yet."))
+                .center_x()
+                .center_y()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+        };
+
+        layout(app, column![header, body].spacing(8).padding(8).into())
+    }
+
+    /// Reads the current log on the blocking thread pool, the lines come back as `LogRead`
+    fn read(&mut self) -> Command<Message> {
+        self.lines = None;
+        let log = self.log;
+
+        Command::perform(
+            async move {
+                let lines =
+                    tokio::task::spawn_blocking(move || logging::tail(LOGS[log], MAX_LINES))
+                        .await
+                        .unwrap();
+                match lines {
+                    Ok(lines) => Ok(lines),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+                    Err(err) => {
+                        tracing::error!("Failed to read {} log: {err:?}", LOGS[log]);
+                        Err(format!("Couldn't read log: {err}"))
+                    }
+                }
+            },
+            move |lines| Message::LogRead(log, lines),
+        )
+    }
+
+    fn set_lines(&mut self, lines: Result<Vec<String>, String>) {
+        let lines = match lines {
+            Ok(lines) => lines,
+            Err(err) => {
+                self.lines = Some(Err(err));
+                return;
+            }
+        };
+
+        self.list = ScrollableList::new(
+            lines
+                .iter()
+                .map(|line| {
+                    let line = line.clone();
+
+                    ListItem::new(
+                        move |_app: &'_ App| text(line.clone()).size(12).into(),
+                        |_app: &'_ mut App, _message: Message| Command::none(),
+                    )
+                })
+                .collect(),
+        );
+        // Newest lines are the interesting ones
+        self.list.select(lines.len().saturating_sub(1));
+        self.lines = Some(Ok(lines));
+    }
+}
@@ -12,7 +12,7 @@ use system::SystemMessage;
 
 use crate::{app::App, layout::layout, Message};
 
-use super::Screen;
+use super::{logs, Screen};
 
 static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
 
@@ -44,11 +44,31 @@ impl Default for State {
                     text: "games",
                     screen: Screen::Games,
                 },
+                MainScreenButton {
+                    icon: "".into(),
+                    text: "search",
+                    screen: Screen::Search,
+                },
                 MainScreenButton {
                     icon: "".into(),
                     text: "settings",
                     screen: Screen::Settings,
                 },
+                MainScreenButton {
+                    icon: "".into(),
+                    text: "stats",
+                    screen: Screen::Stats,
+                },
+                MainScreenButton {
+                    icon: "".into(),
+                    text: "bios",
+                    screen: Screen::Bios,
+                },
+                MainScreenButton {
+                    icon: "".into(),
+                    text: "logs",
+                    screen: Screen::Logs,
+                },
             ],
             selected: 0,
         }
@@ -64,6 +84,9 @@ impl State {
                     Button::A if ev.pressed() => {
                         // Change screen to selected button
                         app.screen = state.buttons[state.selected].screen.clone();
+                        if app.screen == Screen::Logs {
+                            return logs::State::open();
+                        }
                     }
                     Button::Right if ev.pressed() => {
                         // Move to right if possible
@@ -7,11 +7,15 @@ use system::{Init, SystemMessage};
 
 use crate::{app::App, Message};
 
+pub mod bios;
 pub mod favorites;
 pub mod games;
+pub mod logs;
 pub mod main;
 mod playing;
+pub mod search;
 pub mod settings;
+pub mod stats;
 pub mod switcher;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,8 +28,12 @@ pub enum Screen {
     Main,
     Favorites,
     Games,
+    Search,
     Settings,
+    Stats,
     Switcher,
+    Bios,
+    Logs,
 }
 
 impl Screen {
@@ -47,8 +55,12 @@ impl Screen {
             Self::Main => main::State::update(app, message),
             Self::Favorites => favorites::State::update(app, message),
             Self::Games => games::State::update(app, message),
+            Self::Search => search::State::update(app, message),
             Self::Settings => settings::State::update(app, message),
+            Self::Stats => stats::State::update(app, message),
             Self::Switcher => switcher::State::update(app, message),
+            Self::Bios => bios::State::update(app, message),
+            Self::Logs => logs::State::update(app, message),
             Self::Shutdown => Command::none(),
         }
     }
@@ -71,8 +83,12 @@ impl Screen {
             Self::Main => main::State::view(app),
             Self::Favorites => favorites::State::view(app),
             Self::Games => games::State::view(app),
+            Self::Search => search::State::view(app),
             Self::Settings => settings::State::view(app),
+            Self::Stats => stats::State::view(app),
             Self::Switcher => switcher::State::view(app),
+            Self::Bios => bios::State::view(app),
+            Self::Logs => logs::State::view(app),
             Self::Shutdown => container::Container::new(text("powering off..."))
                 .center_x()
                 .center_y()
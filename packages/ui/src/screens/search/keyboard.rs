@@ -0,0 +1,81 @@
+use iced::{
+    border::Radius,
+    color,
+    widget::{column, container, row, text},
+    Background, Border, Element, Length,
+};
+use input::Button;
+
+use crate::Message;
+
+const KEYS: &[&[char]] = &[
+    &['1', '2', '3', '4', '5', '6', '7', '8', '9', '0'],
+    &['q', 'w', 'e', 'r', 't', 'y', 'u', 'i', 'o', 'p'],
+    &['a', 's', 'd', 'f', 'g', 'h', 'j', 'k', 'l', '\''],
+    &['z', 'x', 'c', 'v', 'b', 'n', 'm', '-', '&', '.'],
+];
+
+/// On screen keyboard navigated with the d-pad, wraps around at the edges
+#[derive(Debug, Default)]
+pub struct Keyboard {
+    row: usize,
+    col: usize,
+}
+
+impl Keyboard {
+    /// Moves the cursor for d-pad presses, returns whether the button was used
+    pub fn navigate(&mut self, button: Button) -> bool {
+        let rows = KEYS.len();
+        let cols = KEYS[self.row].len();
+
+        match button {
+            Button::Up => self.row = (self.row + rows - 1) % rows,
+            Button::Down => self.row = (self.row + 1) % rows,
+            Button::Left => self.col = (self.col + cols - 1) % cols,
+            Button::Right => self.col = (self.col + 1) % cols,
+            _ => return false,
+        }
+
+        self.col = self.col.min(KEYS[self.row].len() - 1);
+        true
+    }
+
+    pub fn selected(&self) -> char {
+        KEYS[self.row][self.col]
+    }
+
+    pub fn view(&self, focused: bool) -> Element<'static, Message> {
+        column(KEYS.iter().enumerate().map(|(row_idx, keys)| {
+            row(keys.iter().enumerate().map(|(col_idx, key)| {
+                let selected = focused && row_idx == self.row && col_idx == self.col;
+                key_view(*key, selected)
+            }))
+            .spacing(4)
+            .into()
+        }))
+        .spacing(4)
+        .into()
+    }
+}
+
+fn key_view(key: char, selected: bool) -> Element<'static, Message> {
+    container(text(key).size(20))
+        .center_x()
+        .center_y()
+        .width(Length::Fixed(36.))
+        .height(Length::Fixed(36.))
+        .style(move |_theme: &'_ iced::Theme| container::Appearance {
+            border: Border {
+                radius: Radius::from(4.),
+                width: 1.,
+                color: color!(0x888888),
+            },
+            background: if selected {
+                Some(Background::Color(color!(255, 0, 0)))
+            } else {
+                None
+            },
+            ..Default::default()
+        })
+        .into()
+}
@@ -0,0 +1,240 @@
+mod keyboard;
+
+use std::sync::Arc;
+
+use iced::{
+    color,
+    widget::{column, container, row, text},
+    Command, Element, Length,
+};
+use input::Button;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, MutexGuard};
+use shared_ui::{scrollable_list, ListItem, ScrollableList};
+use system::{
+    emulator::play,
+    games::{
+        search::{search, Filter},
+        Game,
+    },
+    SystemMessage,
+};
+
+use crate::{app::App, layout::layout, Message};
+
+use self::keyboard::Keyboard;
+
+use super::Screen;
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Keyboard,
+    Results,
+}
+
+#[derive(Debug)]
+pub struct State {
+    query: String,
+    filter: Filter,
+    keyboard: Keyboard,
+    focus: Focus,
+    results: Arc<[Game]>,
+    list: ScrollableList<App>,
+    /// Results need to be rebuilt for the current query and filter
+    stale: bool,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            filter: Filter::default(),
+            keyboard: Keyboard::default(),
+            focus: Focus::Keyboard,
+            results: Arc::new([]),
+            list: ScrollableList::new(vec![]),
+            stale: true,
+        }
+    }
+}
+
+impl State {
+    /// Starts a fresh search limited to `filter`, call before switching to this screen
+    pub fn open(filter: Filter) {
+        let mut state = STATE.lock();
+        state.query.clear();
+        state.filter = filter;
+        state.focus = Focus::Keyboard;
+        state.stale = true;
+    }
+
+    pub fn update(app: &mut App, message: Message) -> Command<Message> {
+        let mut state = STATE.lock();
+        state.sync(app);
+
+        let Message::System(SystemMessage::ButtonEvent(ev)) = &message else {
+            return Command::none();
+        };
+
+        if !ev.triggered() {
+            return Command::none();
+        }
+
+        // Filters can be switched from either side
+        if ev.is_pressed(Button::Select) {
+            state.filter = state.filter.next();
+            state.stale = true;
+            return Command::none();
+        }
+
+        match state.focus {
+            Focus::Keyboard => {
+                match ev.button() {
+                    Button::A => {
+                        let key = state.keyboard.selected();
+                        state.query.push(key);
+                        state.stale = true;
+                    }
+                    Button::Y => {
+                        state.query.push(' ');
+                        state.stale = true;
+                    }
+                    Button::X => {
+                        state.query.pop();
+                        state.stale = true;
+                    }
+                    Button::Start if !state.results.is_empty() => {
+                        state.focus = Focus::Results;
+                    }
+                    Button::B => {
+                        app.screen = Screen::Main;
+                    }
+                    button => {
+                        state.keyboard.navigate(*button);
+                    }
+                }
+                Command::none()
+            }
+            Focus::Results => match ev.button() {
+                Button::B | Button::Start => {
+                    state.focus = Focus::Keyboard;
+                    Command::none()
+                }
+                Button::Up => state
+                    .list
+                    .update(app, message, scrollable_list::Message::Up),
+                Button::Down => state
+                    .list
+                    .update(app, message, scrollable_list::Message::Down),
+                Button::L1 => state
+                    .list
+                    .update(app, message, scrollable_list::Message::PageUp),
+                Button::R1 => state
+                    .list
+                    .update(app, message, scrollable_list::Message::PageDown),
+                _ if ev.held() => Command::none(),
+                _ => state
+                    .list
+                    .update(app, message, scrollable_list::Message::Other),
+            },
+        }
+    }
+
+    pub fn view(app: &App) -> Element<Message> {
+        let mut state: MutexGuard<'static, State> = STATE.lock();
+        state.sync(app);
+
+        let results: Element<'static, Message> = if state.results.is_empty() {
+            container(text("No matches").style(color!(0x888888)))
+                .center_x()
+                .center_y()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into()
+        } else {
+            state.list.view(app)
+        };
+
+        layout(
+            app,
+            column![
+                row![
+                    text(format!("{}_", state.query))
+                        .size(24)
+                        .width(Length::Fill),
+                    text(format!("{} ({})", state.filter.name(), state.results.len()))
+                        .size(16)
+                        .style(color!(0x888888)),
+                ]
+                .align_items(iced::Alignment::Center),
+                row![
+                    state.keyboard.view(state.focus == Focus::Keyboard),
+                    container(results).width(Length::Fill).height(Length::Fill)
+                ]
+                .spacing(8),
+                text("A type  X delete  Y space  Select filter  Start results")
+                    .size(14)
+                    .style(color!(0x888888)),
+            ]
+            .spacing(8)
+            .padding(8)
+            .into(),
+        )
+    }
+
+    /// Rebuilds results if the query or filter changed
+    fn sync(&mut self, app: &App) {
+        if !self.stale {
+            return;
+        }
+        self.stale = false;
+
+        let results: Arc<[Game]> =
+            search(&app.games, &app.favorites, &self.query, self.filter).into();
+        self.results = results.clone();
+        self.list = ScrollableList::new(
+            (0..results.len())
+                .map(move |game_idx| {
+                    let children_games = results.clone();
+                    let action_games = results.clone();
+
+                    ListItem::new(
+                        move |_app: &'_ App| {
+                            let game = &children_games[game_idx];
+                            row![
+                                text(game.title()).size(20),
+                                text(game.console().name()).size(14).style(color!(0x888888)),
+                                text(game.metadata().region_badge().unwrap_or_default())
+                                    .size(14)
+                                    .style(color!(0x888888)),
+                            ]
+                            .spacing(8)
+                            .align_items(iced::Alignment::Center)
+                            .into()
+                        },
+                        move |app: &'_ mut App, message: Message| {
+                            let game = &action_games[game_idx];
+
+                            match message {
+                                Message::System(SystemMessage::ButtonEvent(ev))
+                                    if ev.is_pressed(Button::A) =>
+                                {
+                                    play(game);
+                                    app.screen = Screen::Playing(Some(Box::new(Screen::Search)));
+                                    Command::none()
+                                }
+                                _ => Command::none(),
+                            }
+                        },
+                    )
+                })
+                .collect(),
+        );
+
+        if self.results.is_empty() {
+            self.focus = Focus::Keyboard;
+        }
+    }
+}
@@ -1,15 +1,15 @@
-use std::{fmt::Debug, sync::Arc};
-
 use iced::{
-    color, theme,
-    widget::{column, container, row, text, Text},
+    widget::{row, text},
     Command, Element, Length,
 };
 use input::Button;
 use once_cell::sync::Lazy;
 use parking_lot::{Mutex, MutexGuard};
 use shared_ui::{scrollable_list, ListItem, ScrollableList};
-use system::SystemMessage;
+use system::{
+    settings::{self, ClockFormat, Theme, MAX_BRIGHTNESS, MAX_RUMBLE_INTENSITY, MAX_VOLUME},
+    Settings, SystemMessage,
+};
 
 use crate::{app::App, layout::layout, Message};
 
@@ -17,6 +17,15 @@ use super::Screen;
 
 static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
 
+/// Idle timeout choices in seconds, 0 is never
+const IDLE_TIMEOUTS: &[u32] = &[0, 30, 60, 2 * 60, 4 * 60, 5 * 60, 10 * 60, 30 * 60];
+/// Power off timeout choices in seconds, 0 is never
+const POWER_OFF_TIMEOUTS: &[u32] = &[0, 30 * 60, 60 * 60, 2 * 60 * 60, 6 * 60 * 60];
+/// Log filter choices, anything fancier has to be set in the settings file
+const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+/// Filter from the settings file that isn't one of `LOG_LEVELS`, kept as the last choice
+static CUSTOM_LOG_FILTER: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
 #[derive(Debug)]
 pub struct State {
     list: ScrollableList<App>,
@@ -25,35 +34,134 @@ pub struct State {
 impl Default for State {
     fn default() -> Self {
         Self {
-            list: ScrollableList::new(vec![ListItem::new(
-                |app: &'_ App| {
-                    text(format!("Brightness     {}/10", app.settings.brightness))
-                        .size(24)
-                        .into()
-                },
-                |app, message| match message {
-                    Message::System(SystemMessage::ButtonEvent(ev)) => match ev.button() {
-                        Button::Left if ev.pressed() => {
-                            if app.settings.brightness > 0 {
-                                app.settings.update(|settings| {
-                                    settings.brightness -= 1;
-                                });
-                            }
-                            Command::none()
+            list: ScrollableList::new(vec![
+                setting(
+                    "Brightness",
+                    |settings| format!("{}/{MAX_BRIGHTNESS}", settings.brightness),
+                    |settings, increase| {
+                        settings.brightness = step(settings.brightness, increase, MAX_BRIGHTNESS)
+                    },
+                ),
+                setting(
+                    "Volume",
+                    |settings| format!("{}/{MAX_VOLUME}", settings.volume),
+                    |settings, increase| {
+                        settings.volume = step(settings.volume, increase, MAX_VOLUME)
+                    },
+                ),
+                setting(
+                    "Dim after",
+                    |settings| duration(settings.idle.dim_after),
+                    |settings, increase| {
+                        settings.idle.dim_after =
+                            choose(IDLE_TIMEOUTS, settings.idle.dim_after, increase)
+                    },
+                ),
+                setting(
+                    "Then sleep after",
+                    |settings| duration(settings.idle.sleep_after),
+                    |settings, increase| {
+                        settings.idle.sleep_after =
+                            choose(IDLE_TIMEOUTS, settings.idle.sleep_after, increase)
+                    },
+                ),
+                setting(
+                    "In game dim after",
+                    |settings| duration(settings.in_game_idle.dim_after),
+                    |settings, increase| {
+                        settings.in_game_idle.dim_after =
+                            choose(IDLE_TIMEOUTS, settings.in_game_idle.dim_after, increase)
+                    },
+                ),
+                setting(
+                    "In game sleep after",
+                    |settings| duration(settings.in_game_idle.sleep_after),
+                    |settings, increase| {
+                        settings.in_game_idle.sleep_after =
+                            choose(IDLE_TIMEOUTS, settings.in_game_idle.sleep_after, increase)
+                    },
+                ),
+                setting(
+                    "Deep sleep after",
+                    |settings| duration(settings.power_off_timeout),
+                    |settings, increase| {
+                        settings.power_off_timeout =
+                            choose(POWER_OFF_TIMEOUTS, settings.power_off_timeout, increase)
+                    },
+                ),
+                setting(
+                    "Resume game on boot",
+                    |settings| match settings.resume_on_boot {
+                        true => "On".into(),
+                        false => "Off".into(),
+                    },
+                    |settings, _| settings.resume_on_boot = !settings.resume_on_boot,
+                ),
+                setting(
+                    "Rumble",
+                    |settings| match settings.rumble_intensity {
+                        0 => "Off".into(),
+                        intensity => format!("{intensity}/{MAX_RUMBLE_INTENSITY}"),
+                    },
+                    |settings, increase| {
+                        settings.rumble_intensity =
+                            step(settings.rumble_intensity, increase, MAX_RUMBLE_INTENSITY)
+                    },
+                ),
+                setting(
+                    "Theme",
+                    |settings| match settings.theme {
+                        Theme::Dark => "Dark".into(),
+                        Theme::Light => "Light".into(),
+                    },
+                    |settings, _| {
+                        settings.theme = match settings.theme {
+                            Theme::Dark => Theme::Light,
+                            Theme::Light => Theme::Dark,
                         }
-                        Button::Right if ev.pressed() => {
-                            if app.settings.brightness < 10 {
-                                app.settings.update(|settings| {
-                                    settings.brightness += 1;
-                                });
-                            }
-                            Command::none()
+                    },
+                ),
+                setting(
+                    "Clock",
+                    |settings| match settings.clock_format {
+                        ClockFormat::TwentyFourHour => "24 hour".into(),
+                        ClockFormat::TwelveHour => "12 hour".into(),
+                    },
+                    |settings, _| {
+                        settings.clock_format = match settings.clock_format {
+                            ClockFormat::TwentyFourHour => ClockFormat::TwelveHour,
+                            ClockFormat::TwelveHour => ClockFormat::TwentyFourHour,
+                        }
+                    },
+                ),
+                setting(
+                    "Log level",
+                    |settings| settings.log_filter.clone(),
+                    |settings, increase| {
+                        let mut custom = CUSTOM_LOG_FILTER.lock();
+                        if !LOG_LEVELS.contains(&settings.log_filter.as_str()) {
+                            *custom = Some(settings.log_filter.clone());
                         }
-                        _ => Command::none(),
+
+                        let mut choices = LOG_LEVELS.to_vec();
+                        choices.extend(custom.as_deref());
+                        let idx = choices
+                            .iter()
+                            .position(|level| *level == settings.log_filter)
+                            .unwrap_or(0);
+                        settings.log_filter =
+                            choices[step(idx, increase, choices.len() - 1)].into();
                     },
-                    _ => Command::none(),
-                },
-            )]),
+                ),
+                setting(
+                    "Emulator logs",
+                    |settings| match settings.forward_emulator_logs {
+                        true => "In os log".into(),
+                        false => "Own file".into(),
+                    },
+                    |settings, _| settings.forward_emulator_logs = !settings.forward_emulator_logs,
+                ),
+            ]),
         }
     }
 }
@@ -67,12 +175,12 @@ impl State {
                     app.screen = Screen::Main;
                     Command::none()
                 }
-                Button::Up if ev.pressed() => {
+                Button::Up if ev.triggered() => {
                     state
                         .list
                         .update(app, message, scrollable_list::Message::Up)
                 }
-                Button::Down if ev.pressed() => {
+                Button::Down if ev.triggered() => {
                     state
                         .list
                         .update(app, message, scrollable_list::Message::Down)
@@ -90,3 +198,66 @@ impl State {
         layout(app, state.list.view(app))
     }
 }
+
+/// List item showing a setting's value, changed with Left/Right
+fn setting(
+    label: &'static str,
+    value: fn(&Settings) -> String,
+    change: fn(&mut Settings, bool),
+) -> ListItem<App> {
+    ListItem::new(
+        move |app: &'_ App| {
+            row![
+                text(label).size(24).width(Length::Fill),
+                text(value(&app.settings)).size(24)
+            ]
+            .into()
+        },
+        move |app, message| match message {
+            Message::System(SystemMessage::ButtonEvent(ev)) => match ev.button() {
+                Button::Left if ev.triggered() => {
+                    app.settings = settings::update(|settings| change(settings, false));
+                    Command::none()
+                }
+                Button::Right if ev.triggered() => {
+                    app.settings = settings::update(|settings| change(settings, true));
+                    Command::none()
+                }
+                _ => Command::none(),
+            },
+            _ => Command::none(),
+        },
+    )
+}
+
+/// Moves `value` one step within 0 to `max`
+fn step<T>(value: T, increase: bool, max: T) -> T
+where
+    T: PartialOrd + From<u8> + std::ops::Add<Output = T> + std::ops::Sub<Output = T>,
+{
+    if increase && value < max {
+        value + T::from(1)
+    } else if !increase && value > T::from(0) {
+        value - T::from(1)
+    } else {
+        value
+    }
+}
+
+/// Moves to the next or previous choice, starting from the first if `value` isn't one
+fn choose(choices: &[u32], value: u32, increase: bool) -> u32 {
+    let idx = choices
+        .iter()
+        .position(|choice| *choice == value)
+        .unwrap_or(0);
+    choices[step(idx, increase, choices.len() - 1)]
+}
+
+fn duration(secs: u32) -> String {
+    match secs {
+        0 => "Never".into(),
+        secs if secs < 60 => format!("{secs} sec"),
+        secs if secs < 60 * 60 => format!("{} min", secs / 60),
+        secs => format!("{} hr", secs / (60 * 60)),
+    }
+}
@@ -0,0 +1,183 @@
+use std::time::SystemTime;
+
+use iced::{
+    color,
+    widget::{column, container, row, text},
+    Command, Element, Length,
+};
+use input::Button;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, MutexGuard};
+use shared_ui::{scrollable_list, ListItem, ScrollableList};
+use system::{
+    play_time::{self, ConsoleStats, GameStats},
+    SystemMessage,
+};
+
+use crate::{app::App, layout::layout, Message};
+
+use super::Screen;
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+#[derive(Debug, Clone)]
+pub struct State {
+    /// Stats the list was built from, used to rebuild after playing something
+    games: Vec<GameStats>,
+    consoles: Vec<ConsoleStats>,
+    list: ScrollableList<App>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            games: vec![],
+            consoles: vec![],
+            list: ScrollableList::new(vec![]),
+        }
+    }
+}
+
+impl State {
+    pub fn update(app: &mut App, message: Message) -> Command<Message> {
+        let mut state = STATE.lock();
+        state.sync();
+        match &message {
+            Message::System(SystemMessage::ButtonEvent(ev)) => match ev.button() {
+                Button::B if ev.pressed() => {
+                    app.screen = Screen::Main;
+                    Command::none()
+                }
+                Button::Up if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::Up)
+                }
+                Button::Down if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::Down)
+                }
+                Button::L1 if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::PageUp)
+                }
+                Button::R1 if ev.triggered() => {
+                    state
+                        .list
+                        .update(app, message, scrollable_list::Message::PageDown)
+                }
+                _ => Command::none(),
+            },
+            _ => Command::none(),
+        }
+    }
+
+    pub fn view(app: &App) -> Element<Message> {
+        let mut state: MutexGuard<'static, State> = STATE.lock();
+        state.sync();
+
+        if state.games.is_empty() {
+            return layout(
+                app,
+                container(text("Nothing played yet."))
+                    .center_x()
+                    .center_y()
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into(),
+            );
+        }
+
+        let total: u64 = state.consoles.iter().map(|console| console.play_time).sum();
+        let consoles = row(state.consoles.iter().map(|console| {
+            column![
+                text(console.console.name())
+                    .size(14)
+                    .style(color!(0x888888)),
+                text(format_duration(console.play_time)).size(20),
+                text(format!("{} sessions", console.sessions)).size(14),
+            ]
+            .into()
+        }))
+        .spacing(16);
+
+        layout(
+            app,
+            column![
+                text(format!("Total: {}", format_duration(total))).size(24),
+                consoles,
+                state.list.view(app)
+            ]
+            .spacing(8)
+            .padding(8)
+            .into(),
+        )
+    }
+
+    /// Rebuilds the list if play time changed since it was built
+    fn sync(&mut self) {
+        let games = play_time::games();
+        if self.games == games {
+            return;
+        }
+
+        let selected = self.list.selected();
+        self.games = games;
+        self.consoles = play_time::consoles();
+        self.list = ScrollableList::new(
+            self.games
+                .iter()
+                .map(|stats| {
+                    let stats = stats.clone();
+
+                    ListItem::new(
+                        move |_app: &'_ App| {
+                            row![
+                                text(stats.game.title()).size(20).width(Length::Fill),
+                                text(format_duration(stats.play_time)).size(16),
+                                text(format!("{}x", stats.sessions))
+                                    .size(14)
+                                    .style(color!(0x888888)),
+                                text(format_last_played(stats.last_played))
+                                    .size(14)
+                                    .style(color!(0x888888)),
+                            ]
+                            .spacing(8)
+                            .align_items(iced::Alignment::Center)
+                            .into()
+                        },
+                        |_app: &'_ mut App, _message: Message| Command::none(),
+                    )
+                })
+                .collect(),
+        );
+        self.list.select(selected);
+    }
+}
+
+/// `1h 23m`, or just minutes for under an hour
+fn format_duration(secs: u64) -> String {
+    let hours = secs / 3600;
+    let minutes = secs % 3600 / 60;
+
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Rough days since `timestamp`, there is no timezone info to show a real date with
+fn format_last_played(timestamp: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs());
+
+    match now.saturating_sub(timestamp) / (24 * 60 * 60) {
+        0 => "today".into(),
+        1 => "yesterday".into(),
+        days => format!("{days} days ago"),
+    }
+}
@@ -1,62 +1,67 @@
-use std::sync::Arc;
-
 use iced::{
-    color, theme,
-    widget::{column, container, row, text, Text},
+    color,
+    widget::{column, container, image, row, text},
     Command, Element, Length,
 };
 use input::Button;
 use once_cell::sync::Lazy;
 use parking_lot::{Mutex, MutexGuard};
-use system::SystemMessage;
+use system::{
+    emulator::play,
+    history::{self, HistoryEntry},
+    SystemMessage,
+};
 
-use crate::{app::App, layout::layout, Message};
+use crate::{app::App, layout::layout, thumbnail, Message};
 
 use super::Screen;
 
-static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+/// Only the most recent games fit on screen
+const MAX_GAMES: usize = 5;
 
-#[derive(Debug, Clone)]
-struct Game {
-    name: &'static str,
-}
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct State {
-    games: Vec<Game>,
-    /// idx of selected button
+    games: Vec<HistoryEntry>,
+    /// idx of selected game
     selected: usize,
 }
 
-impl Default for State {
-    fn default() -> Self {
-        Self {
-            games: vec![
-                Game { name: "terranigma" },
-                Game { name: "FFVI" },
-                Game { name: "Minish Cap" },
-            ],
-            selected: 0,
-        }
+impl State {
+    /// Reloads recently played games, call when switching to this screen
+    pub fn open() -> Command<Message> {
+        let mut state = STATE.lock();
+        state.games = history::entries();
+        state.games.truncate(MAX_GAMES);
+        state.selected = 0;
+
+        Command::batch(
+            state
+                .games
+                .iter()
+                .map(|entry| thumbnail::load_image(entry.auto_save_screenshot())),
+        )
     }
-}
 
-impl State {
     pub fn update(app: &mut App, message: Message) -> Command<Message> {
         let mut state = STATE.lock();
         match message {
             Message::System(SystemMessage::ButtonEvent(ev)) => {
                 match ev.button() {
                     Button::A if ev.pressed() => {
-                        // Change screen to selected button
-                        let _ = state.games[state.selected].clone();
+                        // Resume selected game, the emulator picks up the auto save
+                        if let Some(entry) = state.games.get(state.selected) {
+                            play(&entry.game);
+                            app.screen = Screen::Playing(Some(Box::new(Screen::Main)));
+                        }
                     }
                     Button::B if ev.pressed() => {
                         app.screen = Screen::Main;
                     }
                     Button::Right if ev.pressed() => {
                         // Move to right if possible
-                        if state.selected < state.games.len() - 1 {
+                        if state.selected + 1 < state.games.len() {
                             state.selected += 1;
                         }
                     }
@@ -76,15 +81,27 @@ impl State {
 
     pub fn view(app: &App) -> Element<Message> {
         let state: MutexGuard<'static, State> = STATE.lock();
+
+        if state.games.is_empty() {
+            return layout(
+                app,
+                container(text("Nothing played yet."))
+                    .center_x()
+                    .center_y()
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .into(),
+            );
+        }
+
         layout(
             app,
             row(state
                 .games
                 .iter()
-                // TODO: remove cloning here somehow????
-                .cloned()
                 .enumerate()
-                .map(|(i, button)| button.view(i == state.selected)))
+                .map(|(i, entry)| card(entry, i == state.selected)))
+            .spacing(8)
             .height(Length::Fill)
             .width(Length::Fill)
             .align_items(iced::Alignment::Center)
@@ -94,24 +111,35 @@ impl State {
     }
 }
 
-impl Game {
-    pub fn view(self, selected: bool) -> Element<'static, Message> {
-        container(
-            column![
-                text("icon here").size(32),
-                text(format!(
-                    "{}{}",
-                    self.name,
-                    // TODO: switch this for a border or some other way to indicate selection
-                    if selected { " selected" } else { "" }
-                ))
-            ]
-            .align_items(iced::Alignment::Center)
-            .spacing(8)
-            .height(Length::Fill)
+fn card(entry: &HistoryEntry, selected: bool) -> Element<'static, Message> {
+    let screenshot: Element<'static, Message> = match thumbnail::get(&entry.auto_save_screenshot())
+    {
+        Some(handle) => image(handle).width(Length::Fill).into(),
+        None => container(text(entry.game.console().name()).size(14))
+            .center_x()
+            .center_y()
             .width(Length::Fill)
-            .padding(8),
-        )
-        .into()
-    }
+            .height(Length::Fixed(96.0))
+            .into(),
+    };
+
+    container(
+        column![
+            screenshot,
+            text(entry.game.title())
+                .size(16)
+                // TODO: switch this for a border or some other way to indicate selection
+                .style(if selected {
+                    color!(0xF74C00)
+                } else {
+                    color!(0xFFFFFF)
+                })
+        ]
+        .align_items(iced::Alignment::Center)
+        .spacing(8)
+        .width(Length::Fill)
+        .padding(8),
+    )
+    .width(Length::FillPortion(1))
+    .into()
 }
@@ -0,0 +1,110 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use iced::{widget::image::Handle, Command};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use system::games::Game;
+
+use crate::Message;
+
+/// Thumbnails are downsized to fit in this box before being cached
+const MAX_WIDTH: u32 = 240;
+const MAX_HEIGHT: u32 = 240;
+/// Decoded thumbnails are ~230KB each, so only keep a handful around
+const CACHE_SIZE: usize = 24;
+
+static CACHE: Lazy<Mutex<Cache>> = Lazy::new(|| Mutex::new(Cache::default()));
+
+#[derive(Debug, Default)]
+struct Cache {
+    /// Game or image path -> thumbnail, `None` means there is no image for it
+    thumbnails: HashMap<PathBuf, Option<Handle>>,
+    /// Oldest entries at the front
+    order: VecDeque<PathBuf>,
+}
+
+/// Returns the cached thumbnail for a path passed to `load` or `load_image`, if it has been loaded
+pub fn get(path: &Path) -> Option<Handle> {
+    CACHE.lock().thumbnails.get(path).cloned().flatten()
+}
+
+/// Stores a thumbnail which was loaded by `load`, evicting the oldest one if full
+pub fn insert(path: PathBuf, handle: Option<Handle>) {
+    let mut cache = CACHE.lock();
+    if cache.thumbnails.insert(path.clone(), handle).is_none() {
+        cache.order.push_back(path);
+    }
+
+    while cache.order.len() > CACHE_SIZE {
+        if let Some(oldest) = cache.order.pop_front() {
+            cache.thumbnails.remove(&oldest);
+        }
+    }
+}
+
+/// Finds and decodes the thumbnail for `game` in the background, does nothing if already cached
+pub fn load(game: &Game) -> Command<Message> {
+    if CACHE.lock().thumbnails.contains_key(game.as_path()) {
+        return Command::none();
+    }
+
+    let game = game.clone();
+    Command::perform(
+        async move {
+            let handle = match game.thumbnail_path().await {
+                Some(image_path) => decode(image_path).await,
+                None => None,
+            };
+            (game.as_path().to_path_buf(), handle)
+        },
+        |(path, handle)| Message::ThumbnailLoaded(path, handle),
+    )
+}
+
+/// Decodes a specific image in the background, cached under its own path
+pub fn load_image(path: PathBuf) -> Command<Message> {
+    if CACHE.lock().thumbnails.contains_key(&path) {
+        return Command::none();
+    }
+
+    Command::perform(
+        async move {
+            let handle = match tokio::fs::try_exists(&path).await {
+                Ok(true) => decode(path.clone()).await,
+                _ => None,
+            };
+            (path, handle)
+        },
+        |(path, handle)| Message::ThumbnailLoaded(path, handle),
+    )
+}
+
+/// Decodes and downsizes the image on the blocking thread pool
+async fn decode(path: PathBuf) -> Option<Handle> {
+    let res = tokio::task::spawn_blocking(move || {
+        let image = image::open(&path)?
+            .thumbnail(MAX_WIDTH, MAX_HEIGHT)
+            .into_rgba8();
+        Ok::<_, image::ImageError>(Handle::from_pixels(
+            image.width(),
+            image.height(),
+            image.into_raw(),
+        ))
+    })
+    .await;
+
+    match res {
+        Ok(Ok(handle)) => Some(handle),
+        Ok(Err(err)) => {
+            tracing::error!("Failed to decode thumbnail: {err:?}");
+            None
+        }
+        Err(err) => {
+            tracing::error!("Thumbnail task failed: {err:?}");
+            None
+        }
+    }
+}